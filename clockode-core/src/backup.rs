@@ -0,0 +1,356 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Rotating copies of the vault, taken right before every save.
+//!
+//! Backups live in a `backups` folder next to the vault, named after the vault and the UTC time
+//! they were taken (`database-20261018-101500.kdbx`). They are plain copies of the encrypted
+//! file, so each one opens with the password the vault had at that time.
+//!
+//! Shared vaults are backed up to the application data directory instead, see
+//! [`shared_backups_dir`], so a synced or team folder doesn't fill up with every member's copies.
+
+use anywho::anywho;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tracing::{info, warn};
+
+use crate::{APP_ID, database::write_private_file, time::format_utc_stamp};
+
+/// Name of the folder next to the vault the backups are kept in
+const BACKUPS_DIR: &str = "backups";
+
+/// How many backups are kept around, older ones are removed after every new backup
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackupPolicy {
+    /// The latest backups to keep, whatever their age
+    pub keep_last: usize,
+    /// For how many days (today included) the last backup of each day is kept too
+    pub keep_days: u32,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: 10,
+            keep_days: 7,
+        }
+    }
+}
+
+impl BackupPolicy {
+    /// Whether no backups are taken at all
+    pub fn is_disabled(&self) -> bool {
+        self.keep_last == 0 && self.keep_days == 0
+    }
+}
+
+/// A backup of the vault found in its backups folder
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// Size of the file in bytes
+    pub size: u64,
+    /// `YYYYMMDD-HHMMSS` UTC stamp of when it was taken, from its file name
+    stamp: String,
+}
+
+impl Backup {
+    /// When the backup was taken, as a `YYYY-MM-DD HH:MM UTC` string
+    pub fn created(&self) -> String {
+        let stamp = &self.stamp;
+        format!(
+            "{}-{}-{} {}:{} UTC",
+            &stamp[0..4],
+            &stamp[4..6],
+            &stamp[6..8],
+            &stamp[9..11],
+            &stamp[11..13]
+        )
+    }
+
+    /// `YYYYMMDD` UTC day the backup was taken
+    fn day(&self) -> &str {
+        &self.stamp[..8]
+    }
+}
+
+/// Folder the backups of the vault at `vault` are kept in
+pub fn backups_dir(vault: &Path) -> Result<PathBuf, anywho::Error> {
+    let dir = vault
+        .parent()
+        .ok_or_else(|| anywho!("Database path has no parent directory"))?;
+
+    Ok(dir.join(BACKUPS_DIR))
+}
+
+/// Folder the backups of the shared vault at `vault` are kept in, one per vault in the
+/// application data directory. It's named after the vault's folder and a hash of its path, so
+/// two shared vaults with the same file name don't prune each other's backups.
+pub fn shared_backups_dir(vault: &Path) -> Result<PathBuf, anywho::Error> {
+    let folder = vault
+        .parent()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    // FNV-1a, stable across Rust releases unlike the std hasher
+    let hash = vault
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+
+    Ok(dirs::data_dir()
+        .ok_or_else(|| anywho!("Could not determine data directory"))?
+        .join(APP_ID)
+        .join(BACKUPS_DIR)
+        .join("shared")
+        .join(format!("{folder}-{hash:016x}")))
+}
+
+/// File name of the vault without its extension, what its backups are named after
+fn vault_stem(vault: &Path) -> Result<String, anywho::Error> {
+    vault
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .ok_or_else(|| anywho!("Database path has no file name"))
+}
+
+/// Copies the vault at `vault`, as it is on disk now, to the backups folder `dir` and removes
+/// the backups the `policy` doesn't keep anymore. Does nothing if the policy is disabled.
+///
+/// Must be called while holding the vault lock, right before replacing the file.
+pub fn back_up_vault(vault: &Path, dir: &Path, policy: BackupPolicy) -> Result<(), anywho::Error> {
+    if policy.is_disabled() || !vault.exists() {
+        return Ok(());
+    }
+
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(dir)
+        .map_err(|e| anywho!("Failed to create the backups folder: {}", e))?;
+
+    let target = dir.join(format!(
+        "{}-{}.kdbx",
+        vault_stem(vault)?,
+        format_utc_stamp(SystemTime::now())
+    ));
+
+    // a backup taken this same second already holds the state from before these saves
+    if !target.exists() {
+        let contents =
+            std::fs::read(vault).map_err(|e| anywho!("Failed to read the vault: {}", e))?;
+        write_private_file(&target, &contents)?;
+        info!("Backed up the vault to {:?}", target);
+    }
+
+    prune_backups(vault, dir, policy)
+}
+
+/// Every backup of the vault at `vault` in the backups folder `dir`, newest first
+pub fn list_backups(vault: &Path, dir: &Path) -> Result<Vec<Backup>, anywho::Error> {
+    let prefix = format!("{}-", vault_stem(vault)?);
+
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(anywho!("Failed to read the backups folder: {}", e)),
+    };
+
+    let mut backups: Vec<Backup> = read_dir
+        .filter_map(Result::ok)
+        .filter_map(|dir_entry| {
+            let file_name = dir_entry.file_name();
+            let stamp = file_name
+                .to_str()?
+                .strip_prefix(&prefix)?
+                .strip_suffix(".kdbx")?;
+            if !is_stamp(stamp) {
+                return None;
+            }
+
+            Some(Backup {
+                path: dir_entry.path(),
+                size: dir_entry.metadata().ok()?.len(),
+                stamp: stamp.to_string(),
+            })
+        })
+        .collect();
+
+    backups.sort_by(|a, b| b.stamp.cmp(&a.stamp));
+    Ok(backups)
+}
+
+/// Whether the given text is a `YYYYMMDD-HHMMSS` stamp, so other files in the folder (or the
+/// backups of a vault whose name starts the same) are left alone
+fn is_stamp(text: &str) -> bool {
+    text.len() == 15
+        && text.char_indices().all(|(index, c)| match index {
+            8 => c == '-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// Removes the backups of the vault at `vault` in `dir` that are neither among the `keep_last`
+/// newest nor the last one of one of the `keep_days` most recent days
+fn prune_backups(vault: &Path, dir: &Path, policy: BackupPolicy) -> Result<(), anywho::Error> {
+    let now = SystemTime::now();
+    let recent_days: HashSet<String> = (0..u64::from(policy.keep_days))
+        .filter_map(|days_ago| now.checked_sub(Duration::from_secs(days_ago * 86_400)))
+        .map(|time| format_utc_stamp(time)[..8].to_string())
+        .collect();
+
+    let mut kept_days = HashSet::new();
+    for (index, backup) in list_backups(vault, dir)?.into_iter().enumerate() {
+        // backups are newest first, so the first one seen of each day is its last one
+        let daily =
+            recent_days.contains(backup.day()) && kept_days.insert(backup.day().to_string());
+
+        if index >= policy.keep_last
+            && !daily
+            && let Err(e) = std::fs::remove_file(&backup.path)
+        {
+            warn!("Failed to remove the old backup {:?}: {}", backup.path, e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh folder holding a vault, named after the test so they can run in parallel
+    fn temp_vault(test: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("clockode-backup-{test}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(BACKUPS_DIR)).unwrap();
+
+        let vault = dir.join("database.kdbx");
+        std::fs::write(&vault, b"current").unwrap();
+        vault
+    }
+
+    fn add_backup(vault: &Path, stamp: &str) {
+        let path = backups_dir(vault)
+            .unwrap()
+            .join(format!("database-{stamp}.kdbx"));
+        std::fs::write(path, b"old").unwrap();
+    }
+
+    fn stamps(vault: &Path) -> Vec<String> {
+        list_backups(vault, &backups_dir(vault).unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.stamp)
+            .collect()
+    }
+
+    fn back_up(vault: &Path, policy: BackupPolicy) {
+        back_up_vault(vault, &backups_dir(vault).unwrap(), policy).unwrap();
+    }
+
+    fn remove_vault(vault: &Path) {
+        std::fs::remove_dir_all(vault.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_last_backups() {
+        let vault = temp_vault("last");
+        let now = SystemTime::now();
+        let old: Vec<String> = (1..=5)
+            .map(|hours| format_utc_stamp(now - Duration::from_secs(hours * 3_600)))
+            .collect();
+        for stamp in &old {
+            add_backup(&vault, stamp);
+        }
+
+        let policy = BackupPolicy {
+            keep_last: 3,
+            keep_days: 0,
+        };
+        back_up(&vault, policy);
+
+        // newest first: the one just taken and the two latest old ones
+        let backups = list_backups(&vault, &backups_dir(&vault).unwrap()).unwrap();
+        assert_eq!(stamps(&vault)[1..], old[..2]);
+        assert!(backups[0].stamp > old[0]);
+        assert_eq!(std::fs::read(&backups[0].path).unwrap(), b"current");
+
+        remove_vault(&vault);
+    }
+
+    #[test]
+    fn keeps_the_last_backup_of_recent_days() {
+        let vault = temp_vault("days");
+        let now = SystemTime::now();
+        let day = |days_ago: u64| {
+            format_utc_stamp(now - Duration::from_secs(days_ago * 86_400))[..8].to_string()
+        };
+        for days_ago in 1..=4 {
+            add_backup(&vault, &format!("{}-100000", day(days_ago)));
+            add_backup(&vault, &format!("{}-200000", day(days_ago)));
+        }
+
+        // today, yesterday and the day before
+        let policy = BackupPolicy {
+            keep_last: 1,
+            keep_days: 3,
+        };
+        back_up(&vault, policy);
+
+        let stamps = stamps(&vault);
+        assert_eq!(
+            stamps[1..],
+            [format!("{}-200000", day(1)), format!("{}-200000", day(2))]
+        );
+        assert_eq!(stamps.len(), 3);
+
+        remove_vault(&vault);
+    }
+
+    #[test]
+    fn leaves_other_files_alone() {
+        let vault = temp_vault("foreign");
+        let dir = backups_dir(&vault).unwrap();
+        assert!(list_backups(&vault, &dir).unwrap().is_empty());
+
+        let foreign = [
+            "notes.txt",
+            "database-old.kdbx",
+            "database-copy-20200101-000000.kdbx",
+            "database-20200101-000000.kdbx.tmp",
+            "other-20200101-000000.kdbx",
+        ];
+        for name in foreign {
+            std::fs::write(dir.join(name), b"foreign").unwrap();
+        }
+
+        let policy = BackupPolicy {
+            keep_last: 1,
+            keep_days: 0,
+        };
+        back_up(&vault, policy);
+        back_up(&vault, policy);
+
+        assert_eq!(list_backups(&vault, &dir).unwrap().len(), 1);
+        for name in foreign {
+            assert!(dir.join(name).exists(), "{name} was removed");
+        }
+
+        remove_vault(&vault);
+    }
+}
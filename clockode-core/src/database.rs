@@ -0,0 +1,1258 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use anywho::anywho;
+use keepass::{Database, DatabaseKey, config::DatabaseVersion};
+use secrecy::{ExposeSecret, SecretString};
+use std::{
+    io::Write,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+use tracing::{info, warn};
+
+use crate::{
+    APP_ID,
+    backup::{self, Backup, BackupPolicy},
+    entry::{ClockodeEntry, update_clockode_entry_in_keepass},
+    merge::{EntryConflict, Resolution, WriteOutcome, changed_since},
+    migration::{MigrationCodes, migration_uris},
+    paper::paper_backup_html,
+};
+
+/// Error message returned when the database can't be opened with the given password
+pub const INCORRECT_PASSWORD: &str = "Incorrect Password";
+
+/// What [`ClockodeDatabase::import_content`] did with the lines of the file
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ImportSummary {
+    /// Entries added to the vault
+    pub imported: usize,
+    /// Lines that aren't a readable `otpauth://` URI, blank lines and `#` comments aside
+    pub skipped: usize,
+}
+
+impl std::fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: usize, one: &'static str, many: &'static str| {
+            if count == 1 { one } else { many }
+        };
+
+        write!(
+            f,
+            "Imported {} {}",
+            self.imported,
+            plural(self.imported, "entry", "entries")
+        )?;
+        if self.skipped > 0 {
+            write!(
+                f,
+                ", skipped {} unreadable {}",
+                self.skipped,
+                plural(self.skipped, "line", "lines")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks whether the application database already exists.
+///
+/// This function looks for the database file in the platform-specific
+/// application data directory under [`APP_ID`].
+///
+/// # Returns
+///
+/// - `Ok(Some(path))` if the database file exists, where `path` is the full
+///   path to the database file.
+/// - `Ok(None)` if the database file does not exist.
+/// - `Err(_)` if the system data directory cannot be determined.
+///
+/// # Errors
+///
+/// Returns an error if the platform-specific data directory is unavailable.
+pub fn check_database() -> Result<Option<PathBuf>, anywho::Error> {
+    let path = dirs::data_dir()
+        .ok_or_else(|| anywho!("Could not determine data directory"))?
+        .join(APP_ID)
+        .join("database.kdbx");
+
+    info!("DATABASE_PATH {:?}", &path);
+
+    if path.exists() {
+        Ok(Some(path))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Takes the advisory lock every Clockode process holds while it reads, changes and saves the
+/// vault at `path`, so two processes can't overwrite each other's edits.
+///
+/// The vault itself gets replaced on every save, so the lock lives on a sidecar file next to it.
+/// It's released when the returned file is dropped. Only saves take it, a read-only handle never
+/// creates the sidecar, so read-only shared folders are left untouched.
+fn lock_vault(path: &std::path::Path) -> Result<std::fs::File, anywho::Error> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anywho!("Database path has no parent directory"))?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anywho!("Database path has no file name"))?;
+    let lock_path = dir.join(format!(".{}.lock", file_name.to_string_lossy()));
+
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| anywho!("Failed to open the vault lock file: {}", e))?;
+    file.lock()
+        .map_err(|e| anywho!("Failed to lock the vault: {}", e))?;
+
+    Ok(file)
+}
+
+/// Temporary file next to `path` with a name no other process or save uses at the same time
+fn unique_tmp_path(path: &std::path::Path) -> Result<PathBuf, anywho::Error> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let dir = path
+        .parent()
+        .ok_or_else(|| anywho!("Path has no parent directory"))?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anywho!("Path has no file name"))?;
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+
+    Ok(dir.join(format!(
+        ".{}.{}-{}-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+        nanos
+    )))
+}
+
+/// Flushes the directory entry of a file that was just renamed into `dir`, without it a crash
+/// right after the rename can bring the old file back
+fn sync_dir(dir: &std::path::Path) -> Result<(), std::io::Error> {
+    #[cfg(unix)]
+    std::fs::File::open(dir)?.sync_all()?;
+
+    // directories can't be opened as files on Windows, renames are durable there already
+    #[cfg(not(unix))]
+    let _ = dir;
+
+    Ok(())
+}
+
+/// Reads the current modification time of a file, if available.
+fn read_mtime(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Saves the database atomically and returns the resulting file mtime
+fn save_database_atomic(
+    db: &mut Database,
+    path: &std::path::Path,
+    password: &SecretString,
+) -> Result<Option<SystemTime>, anywho::Error> {
+    save_database_atomic_with_key(
+        db,
+        path,
+        DatabaseKey::new().with_password(password.expose_secret()),
+    )
+}
+
+/// Same as [`save_database_atomic`] but protecting the file with any [`DatabaseKey`]
+/// (password, key file or both)
+fn save_database_atomic_with_key(
+    db: &mut Database,
+    path: &std::path::Path,
+    key: DatabaseKey,
+) -> Result<Option<SystemTime>, anywho::Error> {
+    db.config.version = DatabaseVersion::KDB4(1);
+
+    // serialize entirely into memory first. If this fails, the file on disk is untouched.
+    let mut buf: Vec<u8> = Vec::new();
+    db.save(&mut buf, key)?;
+
+    // write to a temporary file in the same directory, named uniquely so concurrent saves
+    // (even from other processes) never write to the same temporary file.
+    let dir = path
+        .parent()
+        .ok_or_else(|| anywho!("Database path has no parent directory"))?;
+    let tmp_path = unique_tmp_path(path)?;
+
+    let mtime = {
+        let mut f = std::fs::File::create(&tmp_path)?;
+        f.write_all(&buf)?;
+        // make sure the bytes actually hit the disk before we swap files,
+        f.sync_all()?;
+
+        // capture the mtime the destination file will have after the rename, tthis is what lets callers recognize (and ignore) filesystem-watcher events caused by this very save.
+        f.metadata().and_then(|m| m.modified()).ok()
+    };  // drop file handle
+
+    // replace the old database with the new one
+    if let Err(e) = std::fs::rename(&tmp_path, path) {
+        // remove tmp file
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(anywho!("Failed to replace database file: {}", e));
+    }
+
+    // make the rename itself durable
+    sync_dir(dir).map_err(|e| anywho!("Failed to sync the database directory: {}", e))?;
+
+    Ok(mtime)
+}
+
+/// Writes a file only the current user can read, going through a temporary file and a rename
+/// so a partially written export never shows up at `path`.
+///
+/// Used for every plaintext export, which would otherwise be created with the default umask.
+pub fn write_private_file(path: &std::path::Path, contents: &[u8]) -> Result<(), anywho::Error> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anywho!("Export path has no parent directory"))?;
+    let tmp_path = unique_tmp_path(path)?;
+
+    let result = (|| -> Result<(), std::io::Error> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut f = options.open(&tmp_path)?;
+        // a previous leftover tmp file keeps its old mode, make sure it's private
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            f.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        f.write_all(contents)?;
+        f.sync_all()?;
+        drop(f);
+
+        std::fs::rename(&tmp_path, path)?;
+        sync_dir(dir)
+    })();
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(anywho!("Failed to write {}: {}", path.display(), e));
+    }
+
+    Ok(())
+}
+
+/// Stores `mtime` as the last file state this app instance knows about.
+fn record_known_mtime(slot: &Mutex<Option<SystemTime>>, mtime: Option<SystemTime>) {
+    if let Ok(mut guard) = slot.lock() {
+        *guard = mtime;
+    }
+}
+
+/// Every TOTP entry of the given database, sorted by name
+fn read_entries(db: &Database) -> Result<Vec<ClockodeEntry>, anywho::Error> {
+    let entries = db
+        .root()
+        .group_by_name("Default Group")
+        .map(|g| {
+            let mut v = g
+                .entries()
+                .map(|e| ClockodeEntry::try_from(e.to_owned()))
+                .collect::<Result<Vec<_>, _>>()?;
+            v.sort_by_key(|a| a.name.to_lowercase());
+            Ok::<Vec<ClockodeEntry>, anywho::Error>(v)
+        })
+        .transpose()?
+        .unwrap_or_else(Vec::new);
+
+    Ok(entries)
+}
+
+/// Returns the conflict if the entry with the given id changed on disk since `base` was read,
+/// `ours` being what this instance wants to save (`None` to delete it)
+fn find_conflict(
+    db: &Database,
+    base: &ClockodeEntry,
+    entry_id: uuid::Uuid,
+    ours: Option<&ClockodeEntry>,
+) -> Result<Option<EntryConflict>, anywho::Error> {
+    let theirs = read_entries(db)?
+        .into_iter()
+        .find(|e| e.id == Some(entry_id));
+
+    // deleted on both sides, nothing to decide
+    if ours.is_none() && theirs.is_none() {
+        return Ok(None);
+    }
+
+    if !changed_since(base, theirs.as_ref()) {
+        return Ok(None);
+    }
+
+    Ok(Some(EntryConflict {
+        ours: ours.cloned(),
+        theirs,
+    }))
+}
+
+/// Creates a new empty vault protected by `password` in the application data directory
+/// and returns its path
+pub async fn create_database(password: SecretString) -> Result<PathBuf, anywho::Error> {
+    let path = dirs::data_dir()
+        .ok_or_else(|| anywho!("Could not determine data directory"))?
+        .join(APP_ID)
+        .join("database.kdbx");
+
+    smol::unblock(move || {
+        // Create the directory if it does not exist
+        let dir_path = path
+            .parent()
+            .ok_or_else(|| anywho!("Database path has no parent directory"))?;
+        std::fs::create_dir_all(dir_path)?;
+
+        // another process may have created the vault since this one checked for it
+        let _vault_lock = lock_vault(&path)?;
+        if path.exists() {
+            return Err(anywho!(
+                "A vault was created by another Clockode instance, restart the app to unlock it"
+            ));
+        }
+
+        write_new_vault(&path, &password)?;
+
+        Ok(path)
+    })
+    .await
+}
+
+/// Saves an empty vault with the default group at `path`
+fn write_new_vault(path: &std::path::Path, password: &SecretString) -> Result<(), anywho::Error> {
+    let mut db = Database::new();
+    db.meta.database_name = Some(String::from("Clockode Database"));
+
+    let mut root = db.root_mut();
+    let mut group = root.add_group();
+    group.name = String::from("Default Group");
+
+    save_database_atomic(&mut db, path, password).map(|_| ())
+}
+
+/// Whether the vault at `path` can be saved: the file must be writable and its directory too,
+/// saves go through a temporary file and a rename. Catches read-only mounts and shares.
+///
+/// Only asks the system, nothing is opened for writing: the folder may be watched by a sync
+/// client or another instance, which would react to a probe file.
+#[cfg(unix)]
+fn is_writable(path: &std::path::Path) -> bool {
+    use rustix::fs::{Access, access};
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+
+    access(path, Access::WRITE_OK).is_ok()
+        && access(dir, Access::WRITE_OK | Access::EXEC_OK).is_ok()
+}
+
+/// Whether the vault at `path` can be saved, going by its read-only attribute
+#[cfg(not(unix))]
+fn is_writable(path: &std::path::Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| !metadata.permissions().readonly())
+}
+
+/// Opens the vault at `path`, failing with [`INCORRECT_PASSWORD`] if the password is wrong.
+///
+/// With `read_only`, or if the vault can't be written, the returned handle refuses every change
+/// and never saves the file, see [`ClockodeDatabase::is_read_only`].
+pub async fn unlock_database(
+    path: PathBuf,
+    password: SecretString,
+    read_only: bool,
+) -> Result<ClockodeDatabase, anywho::Error> {
+    smol::unblock(move || {
+        let known_mtime = read_mtime(&path);
+
+        let mut file = std::fs::File::open(&path)?;
+        let key = DatabaseKey::new().with_password(password.expose_secret());
+        let _db = Database::open(&mut file, key).map_err(|e| match e {
+            keepass::error::DatabaseOpenError::Key(_) => anywho!("{}", INCORRECT_PASSWORD),
+            other => other.into(),
+        })?;
+
+        let read_only = read_only || !is_writable(&path);
+        if read_only {
+            info!("Opening the vault read-only");
+        }
+        let backups_dir = backup::backups_dir(&path)?;
+
+        Ok(ClockodeDatabase {
+            path: Box::from(path),
+            password: Box::from(password),
+            lock: Arc::new(Mutex::new(())),
+            known_mtime: Arc::new(Mutex::new(known_mtime)),
+            backup_policy: Arc::new(Mutex::new(BackupPolicy::default())),
+            backups_dir: Box::from(backups_dir),
+            read_only,
+        })
+    })
+    .await
+}
+
+/// Handle to an unlocked vault.
+///
+/// Every operation reopens the file, applies its change and saves it back atomically,
+/// so clones can be shared freely between tasks. Changes other programs made to the file in
+/// the meantime are kept, see [`crate::merge`] for how conflicting edits are handled.
+#[derive(Debug, Clone)]
+pub struct ClockodeDatabase {
+    path: Box<PathBuf>,
+    password: Box<SecretString>,
+    lock: Arc<std::sync::Mutex<()>>, // We use this to prevent Race Condition / Data Loss
+    /// The file mtime after the last save this instance made (or when it was unlocked). Used to tell our own saves apart from changes made by another process.
+    known_mtime: Arc<Mutex<Option<SystemTime>>>,
+    /// Which backups are taken before every save, see [`crate::backup`]
+    backup_policy: Arc<Mutex<BackupPolicy>>,
+    /// Folder the backups are kept in, next to the vault unless changed with
+    /// [`ClockodeDatabase::with_backups_dir`]
+    backups_dir: Box<PathBuf>,
+    /// Opened read-only, on purpose or because the file can't be written
+    read_only: bool,
+}
+
+impl ClockodeDatabase {
+    /// Path of the database file on disk.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Whether the vault was opened read-only, every change fails without touching the file then
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Changes which backups are taken before every save from now on
+    pub fn set_backup_policy(&self, policy: BackupPolicy) {
+        if let Ok(mut guard) = self.backup_policy.lock() {
+            *guard = policy;
+        }
+    }
+
+    /// Keeps the backups of this vault in `dir` instead of next to it, like shared vaults do, see
+    /// [`crate::backup::shared_backups_dir`]
+    pub fn with_backups_dir(mut self, dir: PathBuf) -> Self {
+        self.backups_dir = Box::from(dir);
+        self
+    }
+
+    /// Folder the backups of this vault are kept in
+    pub fn backups_dir(&self) -> &PathBuf {
+        &self.backups_dir
+    }
+
+    fn backup_policy(&self) -> BackupPolicy {
+        self.backup_policy
+            .lock()
+            .map(|guard| *guard)
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if the file on disk differs from the last state this
+    /// instance wrote — i.e. the change came from *another* process.
+    ///
+    /// We uuse this in the filesystem-watcher handler to skip reloads triggered
+    /// by our own saves.
+    ///
+    /// Errs on the side of `true`: if the mtime can't be read (file deleted,
+    /// permissions...), a reload is triggered so the failure surfaces to the
+    /// user instead of being silently swallowed.
+    pub fn has_changed_on_disk(&self) -> bool {
+        let current = read_mtime(&self.path);
+
+        let known = self.known_mtime.lock().ok().and_then(|guard| *guard);
+
+        match (current, known) {
+            (Some(current), Some(known)) => current != known,
+            _ => true,
+        }
+    }
+
+    /// Checks the given password against the database file, used to re-authenticate the user
+    /// before sensitive operations
+    pub async fn verify_password(&self, password: SecretString) -> Result<bool, anywho::Error> {
+        let path = self.path.clone();
+
+        smol::unblock(move || {
+            let mut file = std::fs::File::open(&*path)?;
+            let key = DatabaseKey::new().with_password(password.expose_secret());
+            match Database::open(&mut file, key) {
+                Ok(_) => Ok(true),
+                Err(keepass::error::DatabaseOpenError::Key(_)) => Ok(false),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    /// Name of the vault as stored in the database metadata
+    pub async fn vault_name(&self) -> Result<String, anywho::Error> {
+        let lock = self.lock.clone();
+
+        let path = self.path.clone();
+        let password = self.password.clone();
+
+        smol::unblock(move || {
+            let _guard = lock
+                .lock()
+                .map_err(|e| anywho!("Database lock poisoned: {}", e))?;
+
+            let mut file = std::fs::File::open(&*path)?;
+            let key = DatabaseKey::new().with_password(password.expose_secret());
+            let db = Database::open(&mut file, key)?;
+            drop(file);
+
+            Ok(db
+                .meta
+                .database_name
+                .clone()
+                .unwrap_or_else(|| String::from("Clockode Database")))
+        })
+        .await
+    }
+
+    /// Every TOTP entry stored in the vault, sorted by name
+    pub async fn list_entries(&self) -> Result<Vec<ClockodeEntry>, anywho::Error> {
+        info!("Listing database entries");
+
+        let lock = self.lock.clone();
+
+        let path = self.path.clone();
+        let password = self.password.clone();
+
+        // reading records nothing: it would hide changes made by other programs from the watcher,
+        // and edits carry the entry they started from for conflict checks
+        smol::unblock(move || {
+            let _guard = lock
+                .lock()
+                .map_err(|e| anywho!("Database lock poisoned: {}", e))?;
+
+            let mut file = std::fs::File::open(&*path)?;
+            let key = DatabaseKey::new().with_password(password.expose_secret());
+            let db = Database::open(&mut file, key)?;
+            drop(file); // this should't be needed here because we only read, I just added it for consistency
+
+            read_entries(&db)
+        })
+        .await
+    }
+
+    /// Lists the entries to show them again after a change on disk, unlike [`Self::list_entries`]
+    /// this records the file state it read, so [`Self::has_changed_on_disk`] only reports the
+    /// changes made after it. Otherwise a vault this instance never saves, like a read-only
+    /// shared one, would be reloaded on every filesystem event after its first outside change.
+    pub async fn reload_entries(&self) -> Result<Vec<ClockodeEntry>, anywho::Error> {
+        info!("Reloading database entries");
+
+        let lock = self.lock.clone();
+
+        let path = self.path.clone();
+        let password = self.password.clone();
+        let known_mtime = self.known_mtime.clone();
+
+        smol::unblock(move || {
+            let _guard = lock
+                .lock()
+                .map_err(|e| anywho!("Database lock poisoned: {}", e))?;
+
+            // read before the file, a change saved while reading still counts as a new one
+            let mtime = read_mtime(&path);
+            let mut file = std::fs::File::open(&*path)?;
+            let key = DatabaseKey::new().with_password(password.expose_secret());
+            let db = Database::open(&mut file, key)?;
+            drop(file);
+
+            let entries = read_entries(&db)?;
+            record_known_mtime(&known_mtime, mtime);
+            Ok(entries)
+        })
+        .await
+    }
+
+    /// Adds a single entry to the vault
+    pub async fn add_entry(&self, entry: ClockodeEntry) -> Result<(), anywho::Error> {
+        info!("Adding database entry");
+
+        self.add_entries(vec![entry]).await
+    }
+
+    /// Adds all the given entries with a single save of the database
+    pub async fn add_entries(&self, entries: Vec<ClockodeEntry>) -> Result<(), anywho::Error> {
+        info!("Adding {} database entries", entries.len());
+
+        // new entries can't conflict with anything on disk
+        self.modify(move |db| {
+            let mut root = db.root_mut();
+            let mut target_group = root
+                .group_by_name_mut("Default Group")
+                .ok_or_else(|| anywho!("Default Group not found"))?;
+
+            for entry in entries {
+                let mut keepass_entry = target_group.add_entry();
+                update_clockode_entry_in_keepass(entry, &mut keepass_entry);
+            }
+
+            Ok(WriteOutcome::Written)
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// Replaces the stored entry with the same id, `base` being that entry as it was read
+    /// before the edit.
+    ///
+    /// If the entry on disk no longer matches `base` it was changed by another program meanwhile,
+    /// nothing is saved and the [`EntryConflict`] is returned, see
+    /// [`ClockodeDatabase::resolve_conflict`].
+    pub async fn update_entry(
+        &self,
+        entry: ClockodeEntry,
+        base: ClockodeEntry,
+    ) -> Result<WriteOutcome, anywho::Error> {
+        info!("Updating database entry");
+
+        self.write_update(entry, Some(base)).await
+    }
+
+    /// Removes the given entry, as it was read, from the vault.
+    ///
+    /// If the entry on disk no longer matches it it was changed by another program meanwhile,
+    /// nothing is saved and the [`EntryConflict`] is returned, see
+    /// [`ClockodeDatabase::resolve_conflict`].
+    pub async fn delete_entry(&self, entry: ClockodeEntry) -> Result<WriteOutcome, anywho::Error> {
+        info!("Deleting database entry");
+
+        let entry_id = entry
+            .id
+            .ok_or_else(|| anywho!("Cannot delete entry without UUID"))?;
+        self.write_delete(entry_id, Some(entry)).await
+    }
+
+    /// Settles a conflict returned by [`ClockodeDatabase::update_entry`] or
+    /// [`ClockodeDatabase::delete_entry`] the way the user decided
+    pub async fn resolve_conflict(
+        &self,
+        conflict: EntryConflict,
+        resolution: Resolution,
+    ) -> Result<(), anywho::Error> {
+        info!(
+            "Resolving the conflict on {}: {:?}",
+            conflict.name(),
+            resolution
+        );
+
+        let outcome = match (resolution, conflict.ours, conflict.theirs) {
+            // the version on disk is already there, nothing to write
+            (Resolution::KeepTheirs, _, _) | (Resolution::KeepOurs, None, None) => {
+                WriteOutcome::Written
+            }
+            (Resolution::KeepOurs, Some(ours), Some(_)) => self.write_update(ours, None).await?,
+            // deleted on disk, bring ours back as a new entry
+            (Resolution::KeepOurs, Some(ours), None) => {
+                self.add_entry(ClockodeEntry { id: None, ..ours }).await?;
+                WriteOutcome::Written
+            }
+            (Resolution::KeepOurs, None, Some(theirs)) => {
+                let entry_id = theirs
+                    .id
+                    .ok_or_else(|| anywho!("Cannot delete entry without UUID"))?;
+                self.write_delete(entry_id, None).await?
+            }
+            (Resolution::KeepBoth, Some(ours), Some(_)) => {
+                self.add_entry(ClockodeEntry {
+                    id: None,
+                    name: format!("{} (copy)", ours.name),
+                    ..ours
+                })
+                .await?;
+                WriteOutcome::Written
+            }
+            (Resolution::KeepBoth, _, _) => {
+                return Err(anywho!(
+                    "Both versions can only be kept if the entry still exists on both sides"
+                ));
+            }
+        };
+
+        match outcome {
+            WriteOutcome::Written => Ok(()),
+            WriteOutcome::Conflict(_) => Err(anywho!("The entry changed again, please retry")),
+        }
+    }
+
+    /// Updates the entry, checking first that it still matches `base` on disk if given
+    async fn write_update(
+        &self,
+        entry: ClockodeEntry,
+        base: Option<ClockodeEntry>,
+    ) -> Result<WriteOutcome, anywho::Error> {
+        self.modify(move |db| {
+            let entry_id = entry
+                .id
+                .ok_or_else(|| anywho!("Cannot update entry without UUID"))?;
+
+            if let Some(base) = &base
+                && let Some(conflict) = find_conflict(db, base, entry_id, Some(&entry))?
+            {
+                return Ok(WriteOutcome::Conflict(conflict));
+            }
+
+            let mut root = db.root_mut();
+            let mut target_group = root
+                .group_by_name_mut("Default Group")
+                .ok_or_else(|| anywho!("Default Group not found"))?;
+
+            // Find and update the entry
+            let entry_id = target_group
+                .entry_ids()
+                .find(|e| e.uuid() == entry_id)
+                .ok_or_else(|| anywho!("Entry with UUID {} not found", entry_id))?;
+            let mut entry_found = target_group
+                .entry_mut(entry_id)
+                .ok_or_else(|| anywho!("Entry with UUID {} not found", entry_id))?;
+
+            update_clockode_entry_in_keepass(entry, &mut entry_found);
+
+            Ok(WriteOutcome::Written)
+        })
+        .await
+    }
+
+    /// Deletes the entry, checking first that it still matches `base` on disk if given
+    async fn write_delete(
+        &self,
+        entry_id: uuid::Uuid,
+        base: Option<ClockodeEntry>,
+    ) -> Result<WriteOutcome, anywho::Error> {
+        self.modify(move |db| {
+            if let Some(base) = &base
+                && let Some(conflict) = find_conflict(db, base, entry_id, None)?
+            {
+                return Ok(WriteOutcome::Conflict(conflict));
+            }
+
+            let mut root = db.root_mut();
+            let mut target_group = root
+                .group_by_name_mut("Default Group")
+                .ok_or_else(|| anywho!("Default Group not found"))?;
+
+            let Some(entry_id) = target_group.entry_ids().find(|e| e.uuid() == entry_id) else {
+                // deleted on disk already, the result is the same
+                return Ok(WriteOutcome::Written);
+            };
+            let entry_found = target_group
+                .entry_mut(entry_id)
+                .ok_or_else(|| anywho!("Entry with UUID {} not found", entry_id))?;
+
+            entry_found.remove();
+
+            Ok(WriteOutcome::Written)
+        })
+        .await
+    }
+
+    /// Applies `change` to the vault as it is on disk right now and saves it back.
+    ///
+    /// Nothing is saved if `change` returns a conflict.
+    async fn modify<F>(&self, change: F) -> Result<WriteOutcome, anywho::Error>
+    where
+        F: FnOnce(&mut Database) -> Result<WriteOutcome, anywho::Error> + Send + 'static,
+    {
+        if self.read_only {
+            return Err(anywho!("The vault is open read-only"));
+        }
+
+        let lock = self.lock.clone();
+
+        let path = self.path.clone();
+        let password = self.password.clone();
+        let known_mtime = self.known_mtime.clone();
+        let backup_policy = self.backup_policy();
+        let backups_dir = self.backups_dir.clone();
+
+        smol::unblock(move || {
+            let _guard = lock
+                .lock()
+                .map_err(|e| anywho!("Database lock poisoned: {}", e))?;
+            let _vault_lock = lock_vault(&path)?;
+
+            let mut file = std::fs::File::open(&*path)?;
+            let key = DatabaseKey::new().with_password(password.expose_secret());
+            let mut db = Database::open(&mut file, key)?;
+            drop(file);
+
+            let outcome = change(&mut db)?;
+            if let WriteOutcome::Conflict(conflict) = &outcome {
+                warn!("{} was changed on disk too, not saving", conflict.name());
+                return Ok(outcome);
+            }
+
+            backup::back_up_vault(&path, &backups_dir, backup_policy)
+                .map_err(|e| anywho!("Failed to back up the vault, nothing was saved: {}", e))?;
+            let mtime = save_database_atomic(&mut db, &path, &password)?;
+            record_known_mtime(&known_mtime, mtime);
+
+            Ok(outcome)
+        })
+        .await
+    }
+
+    /// Every backup of the vault, newest first
+    pub async fn list_backups(&self) -> Result<Vec<Backup>, anywho::Error> {
+        let path = self.path.clone();
+        let backups_dir = self.backups_dir.clone();
+
+        smol::unblock(move || backup::list_backups(&path, &backups_dir)).await
+    }
+
+    /// Replaces the vault with the given backup, failing with [`INCORRECT_PASSWORD`] unless
+    /// `password` opens it. The vault is backed up first, so the restore can be undone too.
+    ///
+    /// Returns whether the restored vault still opens with the password this handle was unlocked
+    /// with, if it doesn't the vault has to be unlocked again.
+    pub async fn restore_backup(
+        &self,
+        backup: PathBuf,
+        password: SecretString,
+    ) -> Result<bool, anywho::Error> {
+        if self.read_only {
+            return Err(anywho!("The vault is open read-only"));
+        }
+
+        let lock = self.lock.clone();
+
+        let path = self.path.clone();
+        let current_password = self.password.clone();
+        let known_mtime = self.known_mtime.clone();
+        let backup_policy = self.backup_policy();
+        let backups_dir = self.backups_dir.clone();
+
+        smol::unblock(move || {
+            let _guard = lock
+                .lock()
+                .map_err(|e| anywho!("Database lock poisoned: {}", e))?;
+            let _vault_lock = lock_vault(&path)?;
+
+            let contents =
+                std::fs::read(&backup).map_err(|e| anywho!("Failed to read the backup: {}", e))?;
+            let key = DatabaseKey::new().with_password(password.expose_secret());
+            // only opened to check the password before replacing the vault
+            Database::open(&mut contents.as_slice(), key).map_err(|e| match e {
+                keepass::error::DatabaseOpenError::Key(_) => anywho!("{}", INCORRECT_PASSWORD),
+                other => other.into(),
+            })?;
+
+            backup::back_up_vault(&path, &backups_dir, backup_policy)
+                .map_err(|e| anywho!("Failed to back up the vault, nothing was restored: {}", e))?;
+            write_private_file(&path, &contents)?;
+            info!("Restored the vault from {:?}", backup);
+
+            record_known_mtime(&known_mtime, read_mtime(&path));
+
+            Ok(password.expose_secret() == current_password.expose_secret())
+        })
+        .await
+    }
+
+    /// Imports every `otpauth://` URI (one per line) found in the given file, lines that can't
+    /// be read are skipped and counted in the returned [`ImportSummary`]
+    pub async fn import_content(&self, file_path: PathBuf) -> Result<ImportSummary, anywho::Error> {
+        info!("Importing content to database");
+
+        // Read the import file
+        let content = std::fs::read_to_string(&file_path)
+            .map_err(|e| anywho!("Failed to read import file: {}", e))?;
+
+        let mut entries = Vec::new();
+        let mut skipped = 0;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // we use from_url unchecked because of the same reason we can't use TOTP::new
+            // Don't use TOTP::new() because it enforces validation and some secrets (ej: microsoft)
+            // that are xxxx xxxx xxxx xxxx will fail here if we use ::new() with error:
+            // Failed to construct TOTP object: The length of the shared secret MUST be at least 128 bits. 80 bits is not enough
+            match totp_rs::TOTP::from_url_unchecked(line) {
+                Ok(totp) => {
+                    let name = if totp.account_name.trim().is_empty() {
+                        "Default".to_string()
+                    } else {
+                        totp.account_name.clone()
+                    };
+
+                    entries.push(ClockodeEntry {
+                        id: None,
+                        name,
+                        totp,
+                        modified: None,
+                    });
+                }
+                Err(e) => {
+                    warn!("Warning: Failed to parse TOTP URL '{}': {}", line, e);
+                    skipped += 1;
+                }
+            }
+        }
+
+        let summary = ImportSummary {
+            imported: entries.len(),
+            skipped,
+        };
+
+        // a single save (and backup) for the whole file
+        if !entries.is_empty() {
+            self.add_entries(entries).await?;
+        }
+        Ok(summary)
+    }
+
+    /// Lists the entries with the given ids, fails if none of them is found
+    async fn list_selected_entries(
+        &self,
+        entry_ids: &[uuid::Uuid],
+    ) -> Result<Vec<ClockodeEntry>, anywho::Error> {
+        let entries: Vec<ClockodeEntry> = self
+            .list_entries()
+            .await?
+            .into_iter()
+            .filter(|e| e.id.is_some_and(|id| entry_ids.contains(&id)))
+            .collect();
+
+        if entries.is_empty() {
+            return Err(anywho!("No entries found to export"));
+        }
+
+        Ok(entries)
+    }
+
+    /// Exports the selected entries to the given file as `otpauth://` URIs, one per line
+    pub async fn export_content(
+        &self,
+        file_path: PathBuf,
+        entry_ids: Vec<uuid::Uuid>,
+    ) -> Result<(), anywho::Error> {
+        info!("Exporting database content");
+
+        let entries = self.list_selected_entries(&entry_ids).await?;
+
+        let mut export_content = String::new();
+
+        for entry in entries {
+            let url = entry.totp.get_url();
+            export_content.push_str(&url);
+            export_content.push('\n');
+        }
+
+        write_private_file(&file_path, export_content.as_bytes())
+            .map_err(|e| anywho!("Failed to write export file: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Encodes the selected entries into Google Authenticator `otpauth-migration://` URIs,
+    /// split in batches that each fit in a single QR code
+    pub async fn export_migration_uris(
+        &self,
+        entry_ids: Vec<uuid::Uuid>,
+    ) -> Result<MigrationCodes, anywho::Error> {
+        info!("Exporting database content as migration codes");
+
+        let entries = self.list_selected_entries(&entry_ids).await?;
+
+        let codes = migration_uris(&entries);
+        if codes.uris.is_empty() {
+            return Err(anywho!(
+                "Google Authenticator can't generate the codes of the selected entries, it only supports 30s periods with 6 or 8 digits"
+            ));
+        }
+
+        Ok(codes)
+    }
+
+    /// Writes a printable HTML paper backup of the selected entries to the given filepath
+    pub async fn export_paper_backup(
+        &self,
+        file_path: PathBuf,
+        entry_ids: Vec<uuid::Uuid>,
+    ) -> Result<(), anywho::Error> {
+        info!("Exporting database paper backup");
+
+        let entries = self.list_selected_entries(&entry_ids).await?;
+
+        let vault_name = self.vault_name().await?;
+        let html = paper_backup_html(&vault_name, &entries, SystemTime::now());
+
+        write_private_file(&file_path, html.as_bytes())
+            .map_err(|e| anywho!("Failed to write paper backup: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Writes the selected entries into a brand new KDBX4 vault at the given filepath,
+    /// protected by its own password and/or key file.
+    ///
+    /// The new vault goes through the same atomic save as our own database, so the entries
+    /// never touch the disk unencrypted.
+    pub async fn export_keepass_vault(
+        &self,
+        file_path: PathBuf,
+        entry_ids: Vec<uuid::Uuid>,
+        password: Option<SecretString>,
+        key_file: Option<PathBuf>,
+    ) -> Result<(), anywho::Error> {
+        info!("Exporting database content to a new KeePass vault");
+
+        if password.is_none() && key_file.is_none() {
+            return Err(anywho!("A password or a key file is needed to export a vault"));
+        }
+
+        let entries = self.list_selected_entries(&entry_ids).await?;
+
+        smol::unblock(move || {
+            let mut key = DatabaseKey::new();
+            if let Some(password) = &password {
+                key = key.with_password(password.expose_secret());
+            }
+            if let Some(key_file) = &key_file {
+                let mut file = std::fs::File::open(key_file)
+                    .map_err(|e| anywho!("Failed to open key file: {}", e))?;
+                key = key
+                    .with_keyfile(&mut file)
+                    .map_err(|e| anywho!("Failed to read key file: {}", e))?;
+            }
+
+            let mut db = Database::new();
+            db.meta.database_name = Some(String::from("Clockode Export"));
+
+            let mut root = db.root_mut();
+            let mut group = root.add_group();
+            group.name = String::from("Default Group");
+
+            for entry in entries {
+                let mut keepass_entry = group.add_entry();
+                update_clockode_entry_in_keepass(entry, &mut keepass_entry);
+            }
+
+            let _ = save_database_atomic_with_key(&mut db, &file_path, key)?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use totp_rs::{Algorithm, TOTP};
+
+    use super::*;
+
+    async fn open_without_backups(
+        path: &std::path::Path,
+        password: &SecretString,
+    ) -> ClockodeDatabase {
+        let db = unlock_database(path.to_path_buf(), password.clone(), false)
+            .await
+            .unwrap();
+        db.set_backup_policy(BackupPolicy {
+            keep_last: 0,
+            keep_days: 0,
+        });
+        db
+    }
+
+    #[test]
+    fn import_counts_imported_and_skipped_lines() {
+        let dir = std::env::temp_dir().join(format!("clockode-import-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vault.kdbx");
+        let password = SecretString::from("password");
+        write_new_vault(&path, &password).unwrap();
+
+        let file = dir.join("uris.txt");
+        std::fs::write(
+            &file,
+            "# exported codes\n\
+             otpauth://totp/GitHub:alice?secret=JBSWY3DPEHPK3PXP&issuer=GitHub\n\
+             \n\
+             not a uri\n\
+             otpauth://totp/bob?secret=KRSXG5CTMVRXEZLU\n",
+        )
+        .unwrap();
+        let empty = dir.join("empty.txt");
+        std::fs::write(&empty, "not a uri\n").unwrap();
+
+        smol::block_on(async {
+            let db = open_without_backups(&path, &password).await;
+
+            let summary = db.import_content(file).await.unwrap();
+            assert_eq!(
+                summary,
+                ImportSummary {
+                    imported: 2,
+                    skipped: 1
+                }
+            );
+            assert_eq!(db.list_entries().await.unwrap().len(), 2);
+
+            let summary = db.import_content(empty).await.unwrap();
+            assert_eq!(summary.imported, 0);
+            assert_eq!(db.list_entries().await.unwrap().len(), 2);
+        });
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn background_reads_do_not_hide_changes_made_meanwhile() {
+        let dir = std::env::temp_dir().join(format!("clockode-merge-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vault.kdbx");
+        let password = SecretString::from("password");
+        write_new_vault(&path, &password).unwrap();
+
+        smol::block_on(async {
+            // two instances with the same vault open
+            let here = open_without_backups(&path, &password).await;
+            let there = open_without_backups(&path, &password).await;
+
+            let totp = TOTP::new_unchecked(
+                Algorithm::SHA1,
+                6,
+                1,
+                30,
+                b"12345678901234567890".to_vec(),
+                None,
+                "alice".to_string(),
+            );
+            here.add_entry(ClockodeEntry {
+                id: None,
+                name: "github".to_string(),
+                totp,
+                modified: None,
+            })
+            .await
+            .unwrap();
+            let base = here.list_entries().await.unwrap().remove(0);
+
+            let mut theirs = base.clone();
+            theirs.name = "gitlab".to_string();
+            let outcome = there.update_entry(theirs, base.clone()).await.unwrap();
+            assert!(matches!(outcome, WriteOutcome::Written));
+
+            // a reader like D-Bus or the IPC socket lists the entries in the meantime
+            here.list_entries().await.unwrap();
+
+            let edit = |modified| ClockodeEntry {
+                name: "github (work)".to_string(),
+                modified: Some(modified),
+                ..base.clone()
+            };
+
+            let later = SystemTime::now() + Duration::from_secs(60);
+            let WriteOutcome::Conflict(conflict) =
+                here.update_entry(edit(later), base.clone()).await.unwrap()
+            else {
+                panic!("the change made on disk was overwritten");
+            };
+            assert_eq!(conflict.theirs.as_ref().unwrap().name, "gitlab");
+            assert_eq!(conflict.ours_is_newer(), Some(true));
+
+            let WriteOutcome::Conflict(conflict) =
+                here.update_entry(edit(UNIX_EPOCH), base).await.unwrap()
+            else {
+                panic!("the change made on disk was overwritten");
+            };
+            assert_eq!(conflict.ours_is_newer(), Some(false));
+
+            let entries = here.list_entries().await.unwrap();
+            assert_eq!(entries[0].name, "gitlab");
+        });
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn shared_vaults_stay_untouched_when_read_only() {
+        let dir = std::env::temp_dir().join(format!("clockode-shared-{}", std::process::id()));
+        let share = dir.join("share");
+        std::fs::create_dir_all(&share).unwrap();
+        let path = share.join("team.kdbx");
+        let password = SecretString::from("password");
+        write_new_vault(&path, &password).unwrap();
+
+        smol::block_on(async {
+            let mounted = unlock_database(path.clone(), password.clone(), true)
+                .await
+                .unwrap();
+            let totp = TOTP::new_unchecked(
+                Algorithm::SHA1,
+                6,
+                1,
+                30,
+                b"12345678901234567890".to_vec(),
+                None,
+                "alice".to_string(),
+            );
+            let entry = ClockodeEntry {
+                id: None,
+                name: "github".to_string(),
+                totp,
+                modified: None,
+            };
+
+            // no lock file or backups folder next to a vault that is only read
+            assert!(mounted.add_entry(entry.clone()).await.is_err());
+            let files: Vec<_> = std::fs::read_dir(&share)
+                .unwrap()
+                .map(|dir_entry| dir_entry.unwrap().file_name())
+                .collect();
+            assert_eq!(files, ["team.kdbx"]);
+
+            // a member who can write keeps the backups in their own data folder
+            let member = unlock_database(path.clone(), password.clone(), false)
+                .await
+                .unwrap()
+                .with_backups_dir(dir.join("backups"));
+            member.add_entry(entry).await.unwrap();
+            assert!(!share.join("backups").exists());
+            assert_eq!(member.list_backups().await.unwrap().len(), 1);
+
+            // reloading the change records it, later events without one are ignored
+            assert!(mounted.has_changed_on_disk());
+            let entries = mounted.reload_entries().await.unwrap();
+            assert_eq!(entries[0].name, "github");
+            assert!(!mounted.has_changed_on_disk());
+        });
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
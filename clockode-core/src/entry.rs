@@ -1,3 +1,5 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use anywho::anywho;
 use keepass::db::{Entry, EntryMut, Value};
 use totp_rs::{Algorithm, Secret, TOTP};
@@ -11,12 +13,32 @@ const CUSTOM_PERIOD_KEY: &str = "ClockodeTotpPeriod";
 const CUSTOM_DIGITS_KEY: &str = "ClockodeTotpDigits";
 const CUSTOM_ISSUER_KEY: &str = "ClockodeTotpIssuer";
 const CUSTOM_ACCOUNTNAME_KEY: &str = "ClockodeTotpAccountName";
+const CUSTOM_MODIFIED_KEY: &str = "ClockodeTotpModified";
 
+/// A TOTP entry as stored in the vault
 #[derive(Debug, Clone)]
 pub struct ClockodeEntry {
+    /// KeePass entry id, `None` until the entry has been saved
     pub id: Option<Uuid>,
+    /// Display name, the KeePass entry title
     pub name: String,
+    /// Code generator with the secret and parameters of the entry
     pub totp: TOTP,
+    /// When Clockode last saved the entry, `None` for entries saved by older versions or other apps
+    pub modified: Option<SystemTime>,
+}
+
+impl ClockodeEntry {
+    /// Whether both entries hold the same name, secret and code parameters
+    pub fn same_content(&self, other: &ClockodeEntry) -> bool {
+        self.name == other.name
+            && self.totp.algorithm == other.totp.algorithm
+            && self.totp.digits == other.totp.digits
+            && self.totp.step == other.totp.step
+            && self.totp.secret == other.totp.secret
+            && self.totp.issuer == other.totp.issuer
+            && self.totp.account_name == other.totp.account_name
+    }
 }
 
 impl TryFrom<Entry> for ClockodeEntry {
@@ -67,6 +89,11 @@ impl TryFrom<Entry> for ClockodeEntry {
             .unwrap_or(&name)
             .to_string();
 
+        let modified = value
+            .get(CUSTOM_MODIFIED_KEY)
+            .and_then(|s| s.parse().ok())
+            .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds));
+
         // Don't use TOTP::new() because it enforces validation and some secrets (ej: microsoft)
         // that are xxxx xxxx xxxx xxxx will fail here if we use ::new() with error:
         // Failed to construct TOTP object: The length of the shared secret MUST be at least 128 bits. 80 bits is not enough
@@ -84,10 +111,12 @@ impl TryFrom<Entry> for ClockodeEntry {
             id: Some(id),
             name,
             totp: totp_result,
+            modified,
         })
     }
 }
 
+/// Writes the given entry into the KeePass entry, stamping it as modified now
 pub fn update_clockode_entry_in_keepass(value: ClockodeEntry, entry: &mut EntryMut) {
     entry
         .fields
@@ -124,6 +153,44 @@ pub fn update_clockode_entry_in_keepass(value: ClockodeEntry, entry: &mut EntryM
         CUSTOM_ACCOUNTNAME_KEY.to_string(),
         Value::Unprotected(value.totp.account_name),
     );
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    entry.fields.insert(
+        CUSTOM_MODIFIED_KEY.to_string(),
+        Value::Unprotected(now.to_string()),
+    );
+}
+
+/// Entries whose name or issuer matches `query` (case insensitive).
+///
+/// Exact matches win over partial ones: if any entry is named exactly `query` only those
+/// are returned, otherwise every entry containing it.
+pub fn find_entries<'a>(entries: &'a [ClockodeEntry], query: &str) -> Vec<&'a ClockodeEntry> {
+    let query = query.to_lowercase();
+    let fields = |entry: &'a ClockodeEntry| {
+        [
+            Some(entry.name.to_lowercase()),
+            entry.totp.issuer.as_ref().map(|issuer| issuer.to_lowercase()),
+        ]
+        .into_iter()
+        .flatten()
+    };
+
+    let exact: Vec<&ClockodeEntry> = entries
+        .iter()
+        .filter(|entry| fields(entry).any(|field| field == query))
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    entries
+        .iter()
+        .filter(|entry| fields(entry).any(|field| field.contains(&query)))
+        .collect()
 }
 
 // impl From<ClockodeEntry> for Entry {
@@ -1,21 +1,36 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use crate::app::core::ClockodeEntry;
+use crate::{
+    ClockodeEntry,
+    qr::{render_qr_png, render_qr_svg},
+};
+use std::time::SystemTime;
+
 use anywho::anywho;
 use totp_rs::{Algorithm, TOTP};
 use uuid::Uuid;
 
+/// Hash algorithms an entry can use, in the order they are offered to the user
 pub const ALL_ALGORITHMS: &[Algorithm] = &[Algorithm::SHA1, Algorithm::SHA256, Algorithm::SHA512];
 
+/// Editable form of a [`ClockodeEntry`], with the secret kept as the base32 text the user typed
 #[derive(Debug, Clone)]
 pub struct InputableClockodeEntry {
+    /// Id of the entry being edited, `None` for a new one
     pub uuid: Option<Uuid>,
+    /// Display name
     pub name: String,
+    /// Hash algorithm, one of [`ALL_ALGORITHMS`]
     pub algorithm: Algorithm,
+    /// Code length, 6 or 8
     pub digits: usize,
+    /// Seconds each code is valid for
     pub step: u64,
+    /// Base32 encoded shared secret
     pub secret: String,
+    /// Service the account belongs to
     pub issuer: Option<String>,
+    /// Account the codes are for, usually an email or username
     pub account_name: String,
 }
 
@@ -74,6 +89,8 @@ impl TryFrom<InputableClockodeEntry> for ClockodeEntry {
                 issuer: value.issuer,
                 account_name: value.account_name,
             },
+            // Submitting the form is when the edit was made, so conflicts can tell which side is newer
+            modified: Some(SystemTime::now()),
         };
 
         Ok(entry)
@@ -142,7 +159,27 @@ impl InputableClockodeEntry {
         true
     }
 
+    /// QR code of the entry as a small PNG, used for the preview in the entry form
     pub fn get_qr_bytes(&self) -> Result<Vec<u8>, anywho::Error> {
+        let qr = self
+            .totp()?
+            .get_qr_png()
+            .map_err(|e| anywho!("Error generating the QR Code: {}", e))?;
+
+        Ok(qr)
+    }
+
+    /// QR code of the entry as a PNG of roughly `size` pixels per side
+    pub fn get_qr_png(&self, size: u32) -> Result<Vec<u8>, anywho::Error> {
+        render_qr_png(&self.totp()?.get_url(), size)
+    }
+
+    /// QR code of the entry as an SVG document
+    pub fn get_qr_svg(&self) -> Result<String, anywho::Error> {
+        render_qr_svg(&self.totp()?.get_url())
+    }
+
+    fn totp(&self) -> Result<TOTP, anywho::Error> {
         if !self.valid() {
             return Err(anywho!("Invalid Entity"));
         };
@@ -151,7 +188,7 @@ impl InputableClockodeEntry {
             .to_bytes()
             .map_err(|e| anywho!("Failed to decode TOTP secret from KeePass entry: {}", e))?;
 
-        let totp = TOTP {
+        Ok(TOTP {
             algorithm: self.algorithm,
             digits: self.digits,
             skew: 1,
@@ -159,12 +196,6 @@ impl InputableClockodeEntry {
             secret: secret_bytes,
             issuer: self.issuer.clone(),
             account_name: self.account_name.clone(),
-        };
-
-        let qr = totp
-            .get_qr_png()
-            .map_err(|e| anywho!("Error generating the QR Code: {}", e))?;
-
-        Ok(qr)
+        })
     }
 }
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Everything Clockode does with a vault that does not need a window.
+//!
+//! Opening, creating and saving the KeePass database, mapping its entries to TOTP codes,
+//! parsing and rendering QR codes and the various import/export formats all live here,
+//! so the Iced application and the command line interface share the exact same behaviour.
+
+mod backup;
+mod database;
+mod entry;
+mod input;
+mod merge;
+mod migration;
+mod paper;
+mod qr;
+mod time;
+
+/// Application identifier, also the name of the data directory the vault and config live in
+pub const APP_ID: &str = "dev.mariinkys.Clockode";
+
+pub use backup::Backup;
+pub use backup::BackupPolicy;
+pub use backup::backups_dir;
+pub use backup::shared_backups_dir;
+
+pub use database::ClockodeDatabase;
+pub use database::INCORRECT_PASSWORD;
+pub use database::ImportSummary;
+pub use database::check_database;
+pub use database::create_database;
+pub use database::unlock_database;
+pub use database::write_private_file;
+
+pub use entry::ClockodeEntry;
+pub use entry::find_entries;
+
+pub use input::ALL_ALGORITHMS;
+pub use input::InputableClockodeEntry;
+
+pub use merge::EntryConflict;
+pub use merge::Resolution;
+pub use merge::WriteOutcome;
+
+pub use migration::MigrationCodes;
+pub use migration::is_migration_compatible;
+
+pub use qr::QR_IMAGE_EXTENSIONS;
+pub use qr::decode_qr_codes;
+pub use qr::is_qr_image;
+pub use qr::read_qr_from_file;
+pub use qr::read_qr_from_image;
+pub use qr::render_qr_png;
+pub use qr::render_qr_svg;
+
+pub use time::format_utc_datetime;
+pub use time::get_time_until_next_totp_refresh;
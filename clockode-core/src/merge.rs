@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Merging the change of this instance with whatever another program (a sync tool, another
+//! window...) wrote to the vault since it was last read.
+//!
+//! Like KeePass does it, entries are matched by their UUID. Every write reopens the file and only
+//! applies its own change, so additions and edits of other entries are never lost. The only real
+//! conflict is an entry that was changed both here and on disk, that one is handed back to the
+//! user as an [`EntryConflict`] instead of being overwritten. To tell, every edit carries the
+//! entry as it was read when the edit started and compares it with the entry on disk.
+
+use crate::ClockodeEntry;
+
+/// An entry changed (or deleted) both by this instance and on disk since the edit started
+#[derive(Debug, Clone)]
+pub struct EntryConflict {
+    /// The entry as this instance wanted to save it, `None` if it was deleted here
+    pub ours: Option<ClockodeEntry>,
+    /// The entry as it is on disk now, `None` if it was deleted there
+    pub theirs: Option<ClockodeEntry>,
+}
+
+impl EntryConflict {
+    /// Name to show for the conflicting entry
+    pub fn name(&self) -> &str {
+        self.ours
+            .as_ref()
+            .or(self.theirs.as_ref())
+            .map(|entry| entry.name.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Whether our version was saved after theirs, going by the modification times. `None` if
+    /// they can't be compared (one side deleted or saved by something that doesn't stamp entries).
+    pub fn ours_is_newer(&self) -> Option<bool> {
+        let ours = self.ours.as_ref()?.modified?;
+        let theirs = self.theirs.as_ref()?.modified?;
+        Some(ours >= theirs)
+    }
+}
+
+/// How the user decided to settle an [`EntryConflict`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    /// Save our version (or deletion) over the one on disk
+    KeepOurs,
+    /// Drop our change and keep the version on disk
+    KeepTheirs,
+    /// Keep the version on disk and save ours as a separate copy, only when both still exist
+    KeepBoth,
+}
+
+/// Result of a write that may have run into a conflict
+#[derive(Debug, Clone)]
+pub enum WriteOutcome {
+    /// The change was saved
+    Written,
+    /// Nothing was saved, the user has to settle the conflict first
+    Conflict(EntryConflict),
+}
+
+/// Whether an entry changed between the `base` an edit started from and its `current` state on
+/// disk (`None` if it was deleted). Modification times catch edits made by other Clockode
+/// instances, comparing the contents catches edits made by apps that don't stamp entries.
+pub fn changed_since(base: &ClockodeEntry, current: Option<&ClockodeEntry>) -> bool {
+    current.is_none_or(|current| base.modified != current.modified || !base.same_content(current))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use totp_rs::{Algorithm, TOTP};
+
+    use super::*;
+
+    fn entry(name: &str, modified: Option<u64>) -> ClockodeEntry {
+        ClockodeEntry {
+            id: Some(uuid::Uuid::nil()),
+            name: name.to_string(),
+            totp: TOTP::new_unchecked(
+                Algorithm::SHA1,
+                6,
+                1,
+                30,
+                b"12345678901234567890".to_vec(),
+                None,
+                name.to_string(),
+            ),
+            modified: modified.map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds)),
+        }
+    }
+
+    #[test]
+    fn unchanged_entry_is_not_a_change() {
+        let base = entry("github", Some(100));
+
+        assert!(!changed_since(&base, Some(&base)));
+    }
+
+    #[test]
+    fn edited_or_deleted_entry_is_a_change() {
+        let base = entry("github", Some(100));
+
+        // stamped by another Clockode instance
+        assert!(changed_since(&base, Some(&entry("github", Some(200)))));
+        // edited by an app that doesn't stamp entries
+        assert!(changed_since(&base, Some(&entry("gitlab", Some(100)))));
+        assert!(changed_since(&base, None));
+    }
+
+    #[test]
+    fn conflict_reports_the_newer_side() {
+        let conflict = |ours, theirs| EntryConflict {
+            ours: Some(entry("ours", ours)),
+            theirs: Some(entry("theirs", theirs)),
+        };
+
+        assert_eq!(conflict(Some(200), Some(100)).ours_is_newer(), Some(true));
+        assert_eq!(conflict(Some(100), Some(200)).ours_is_newer(), Some(false));
+        assert_eq!(conflict(Some(100), None).ours_is_newer(), None);
+    }
+}
@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use totp_rs::Algorithm;
+use tracing::warn;
+
+use crate::ClockodeEntry;
+
+const MIGRATION_URI_PREFIX: &str = "otpauth-migration://offline?data=";
+
+/// Maximum amount of encoded `OtpParameters` bytes we put in a single batch.
+///
+/// Once base64 and percent encoded this keeps every URI well under the capacity of a QR code
+/// that a phone camera can still read from a laptop screen.
+const MAX_BATCH_BYTES: usize = 600;
+
+// Google Authenticator's `MigrationPayload` protobuf enum values
+const ALGORITHM_SHA1: u64 = 1;
+const ALGORITHM_SHA256: u64 = 2;
+const ALGORITHM_SHA512: u64 = 3;
+const DIGITS_SIX: u64 = 1;
+const DIGITS_EIGHT: u64 = 2;
+const OTP_TYPE_TOTP: u64 = 2;
+
+/// Entries encoded as Google Authenticator migration codes
+#[derive(Debug, Clone, Default)]
+pub struct MigrationCodes {
+    /// One `otpauth-migration://` URI per QR code, in order
+    pub uris: Vec<String>,
+    /// Names of the entries that were left out, Google Authenticator would generate wrong
+    /// codes for them
+    pub skipped: Vec<String>,
+}
+
+/// Encodes the given entries into Google Authenticator `otpauth-migration://` URIs.
+///
+/// Entries are split into as many batches as needed so that each URI fits in a single QR code,
+/// every batch carries its index and the total batch count so the phone can tell when the
+/// transfer is complete. Entries Google Authenticator can't represent are left out and listed
+/// in [`MigrationCodes::skipped`].
+pub fn migration_uris(entries: &[ClockodeEntry]) -> MigrationCodes {
+    let (compatible, skipped): (Vec<&ClockodeEntry>, Vec<&ClockodeEntry>) = entries
+        .iter()
+        .partition(|entry| is_migration_compatible(entry));
+    let skipped: Vec<String> = skipped
+        .into_iter()
+        .map(|entry| {
+            warn!(
+                "Leaving {} out of the migration codes, Google Authenticator can't generate its codes",
+                entry.name
+            );
+            entry.name.clone()
+        })
+        .collect();
+
+    let encoded: Vec<Vec<u8>> = compatible.into_iter().map(encode_otp_parameters).collect();
+
+    // Greedily group the encoded entries so each batch stays under MAX_BATCH_BYTES
+    let mut batches: Vec<Vec<&[u8]>> = Vec::new();
+    let mut current: Vec<&[u8]> = Vec::new();
+    let mut current_len = 0;
+    for params in &encoded {
+        if !current.is_empty() && current_len + params.len() > MAX_BATCH_BYTES {
+            batches.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        current_len += params.len();
+        current.push(params);
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+
+    let batch_id = batch_id();
+    let batch_size = batches.len() as u64;
+
+    let uris = batches
+        .into_iter()
+        .enumerate()
+        .map(|(index, batch)| {
+            let mut payload = Vec::new();
+            for params in batch {
+                put_bytes(&mut payload, 1, params);
+            }
+            put_varint_field(&mut payload, 2, 1); // version
+            put_varint_field(&mut payload, 3, batch_size);
+            put_varint_field(&mut payload, 4, index as u64);
+            put_varint_field(&mut payload, 5, batch_id);
+
+            let data = STANDARD
+                .encode(payload)
+                .replace('+', "%2B")
+                .replace('/', "%2F")
+                .replace('=', "%3D");
+
+            format!("{MIGRATION_URI_PREFIX}{data}")
+        })
+        .collect();
+
+    MigrationCodes { uris, skipped }
+}
+
+/// Whether Google Authenticator generates the same codes as Clockode for the entry. The
+/// migration format has no field for the period (always 30 seconds) and only knows 6 or 8 digits.
+pub fn is_migration_compatible(entry: &ClockodeEntry) -> bool {
+    entry.totp.step == 30 && matches!(entry.totp.digits, 6 | 8)
+}
+
+/// Encodes a single entry as an `OtpParameters` protobuf message
+fn encode_otp_parameters(entry: &ClockodeEntry) -> Vec<u8> {
+    let totp = &entry.totp;
+
+    let algorithm = match totp.algorithm {
+        Algorithm::SHA1 => ALGORITHM_SHA1,
+        Algorithm::SHA256 => ALGORITHM_SHA256,
+        Algorithm::SHA512 => ALGORITHM_SHA512,
+    };
+
+    let digits = if totp.digits == 8 {
+        DIGITS_EIGHT
+    } else {
+        DIGITS_SIX
+    };
+
+    let mut buf = Vec::new();
+    put_bytes(&mut buf, 1, &totp.secret);
+    put_bytes(&mut buf, 2, totp.account_name.as_bytes());
+    if let Some(issuer) = totp.issuer.as_deref().filter(|i| !i.is_empty()) {
+        put_bytes(&mut buf, 3, issuer.as_bytes());
+    }
+    put_varint_field(&mut buf, 4, algorithm);
+    put_varint_field(&mut buf, 5, digits);
+    put_varint_field(&mut buf, 6, OTP_TYPE_TOTP);
+    buf
+}
+
+/// Identifier shared by every batch of one export, only has to differ between exports
+fn batch_id() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() ^ d.as_secs() as u32)
+        .unwrap_or_default();
+
+    // batch_id is an int32 on the protobuf side, keep it positive
+    u64::from(nanos & 0x7fff_ffff)
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_varint_field(buf: &mut Vec<u8>, field: u64, value: u64) {
+    put_varint(buf, field << 3);
+    put_varint(buf, value);
+}
+
+fn put_bytes(buf: &mut Vec<u8>, field: u64, data: &[u8]) {
+    put_varint(buf, (field << 3) | 2);
+    put_varint(buf, data.len() as u64);
+    buf.extend_from_slice(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use totp_rs::TOTP;
+
+    use super::*;
+
+    /// A decoded protobuf field, only the wire types the migration payload uses
+    #[derive(Debug, Clone, PartialEq)]
+    enum Field {
+        Varint(u64),
+        Bytes(Vec<u8>),
+    }
+
+    fn read_varint(data: &[u8], position: &mut usize) -> u64 {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = data[*position];
+            *position += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte < 0x80 {
+                return value;
+            }
+            shift += 7;
+        }
+    }
+
+    /// Every `(field number, value)` of a protobuf message, in order
+    fn read_message(data: &[u8]) -> Vec<(u64, Field)> {
+        let mut fields = Vec::new();
+        let mut position = 0;
+        while position < data.len() {
+            let key = read_varint(data, &mut position);
+            let field = match key & 7 {
+                0 => Field::Varint(read_varint(data, &mut position)),
+                2 => {
+                    let len = read_varint(data, &mut position) as usize;
+                    position += len;
+                    Field::Bytes(data[position - len..position].to_vec())
+                }
+                wire_type => panic!("unexpected wire type {wire_type}"),
+            };
+            fields.push((key >> 3, field));
+        }
+        fields
+    }
+
+    /// The `MigrationPayload` message carried by a migration URI
+    fn read_payload(uri: &str) -> Vec<(u64, Field)> {
+        let data = uri
+            .strip_prefix(MIGRATION_URI_PREFIX)
+            .unwrap()
+            .replace("%2B", "+")
+            .replace("%2F", "/")
+            .replace("%3D", "=");
+        read_message(&STANDARD.decode(data).unwrap())
+    }
+
+    fn varint(fields: &[(u64, Field)], number: u64) -> Option<u64> {
+        fields.iter().find_map(|(n, field)| match field {
+            Field::Varint(value) if *n == number => Some(*value),
+            _ => None,
+        })
+    }
+
+    fn otp_parameters(fields: &[(u64, Field)]) -> Vec<Vec<(u64, Field)>> {
+        fields
+            .iter()
+            .filter_map(|(n, field)| match field {
+                Field::Bytes(bytes) if *n == 1 => Some(read_message(bytes)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn entry(name: &str, algorithm: Algorithm, digits: usize, step: u64) -> ClockodeEntry {
+        ClockodeEntry {
+            id: None,
+            name: name.to_string(),
+            totp: TOTP::new_unchecked(
+                algorithm,
+                digits,
+                1,
+                step,
+                b"12345678901234567890".to_vec(),
+                None,
+                name.to_string(),
+            ),
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn encodes_entries_as_migration_payload() {
+        let mut github = entry("alice@github", Algorithm::SHA256, 8, 30);
+        github.totp.issuer = Some("GitHub".to_string());
+        let entries = [
+            github,
+            entry("bob", Algorithm::SHA1, 6, 30),
+            entry("slow", Algorithm::SHA1, 6, 60),
+            entry("seven", Algorithm::SHA1, 7, 30),
+        ];
+
+        let codes = migration_uris(&entries);
+        assert_eq!(codes.skipped, ["slow", "seven"]);
+        assert_eq!(codes.uris.len(), 1);
+
+        let payload = read_payload(&codes.uris[0]);
+        assert_eq!(varint(&payload, 2), Some(1)); // version
+        assert_eq!(varint(&payload, 3), Some(1)); // batch_size
+        assert_eq!(varint(&payload, 4), Some(0)); // batch_index
+        assert!(varint(&payload, 5).is_some_and(|id| id <= i32::MAX as u64));
+
+        let secret = Field::Bytes(b"12345678901234567890".to_vec());
+        let parameters = otp_parameters(&payload);
+        assert_eq!(
+            parameters[0],
+            [
+                (1, secret.clone()),
+                (2, Field::Bytes(b"alice@github".to_vec())),
+                (3, Field::Bytes(b"GitHub".to_vec())),
+                (4, Field::Varint(ALGORITHM_SHA256)),
+                (5, Field::Varint(DIGITS_EIGHT)),
+                (6, Field::Varint(OTP_TYPE_TOTP)),
+            ]
+        );
+        // no issuer field when there is none
+        assert_eq!(
+            parameters[1],
+            [
+                (1, secret),
+                (2, Field::Bytes(b"bob".to_vec())),
+                (4, Field::Varint(ALGORITHM_SHA1)),
+                (5, Field::Varint(DIGITS_SIX)),
+                (6, Field::Varint(OTP_TYPE_TOTP)),
+            ]
+        );
+        assert_eq!(parameters.len(), 2);
+    }
+
+    #[test]
+    fn splits_large_exports_into_batches() {
+        let entries: Vec<ClockodeEntry> = (0..40)
+            .map(|index| {
+                let name = format!("account-{index:02}@a-rather-long-domain-name.example.com");
+                entry(&name, Algorithm::SHA1, 6, 30)
+            })
+            .collect();
+
+        let codes = migration_uris(&entries);
+        assert!(codes.uris.len() > 1);
+
+        let payloads: Vec<_> = codes.uris.iter().map(|uri| read_payload(uri)).collect();
+        let batch_id = varint(&payloads[0], 5);
+        let mut names = Vec::new();
+        for (index, payload) in payloads.iter().enumerate() {
+            assert_eq!(varint(payload, 3), Some(codes.uris.len() as u64));
+            assert_eq!(varint(payload, 4), Some(index as u64));
+            assert_eq!(varint(payload, 5), batch_id);
+
+            let batch_bytes: usize = payload
+                .iter()
+                .map(|(n, field)| match field {
+                    Field::Bytes(bytes) if *n == 1 => bytes.len(),
+                    _ => 0,
+                })
+                .sum();
+            assert!(batch_bytes <= MAX_BATCH_BYTES);
+
+            for parameters in otp_parameters(payload) {
+                let Some((_, Field::Bytes(name))) = parameters.iter().find(|(n, _)| *n == 2) else {
+                    panic!("entry without a name");
+                };
+                names.push(String::from_utf8(name.clone()).unwrap());
+            }
+        }
+
+        // every entry exactly once, in order
+        let expected: Vec<String> = entries.into_iter().map(|entry| entry.name).collect();
+        assert_eq!(names, expected);
+    }
+}
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use std::time::SystemTime;
+use tracing::warn;
+
+use crate::{ClockodeEntry, InputableClockodeEntry, format_utc_datetime};
+
+const STYLESHEET: &str = r#"
+body { font-family: sans-serif; color: #000; background: #fff; margin: 2em; }
+header { border-bottom: 2px solid #000; margin-bottom: 1.5em; }
+header h1 { margin: 0 0 0.25em 0; }
+header p { margin: 0.25em 0; }
+.entry { display: flex; gap: 1.5em; align-items: center; border: 1px solid #000; border-radius: 8px; padding: 1em; margin-bottom: 1em; page-break-inside: avoid; break-inside: avoid; }
+.entry img { width: 180px; height: 180px; image-rendering: pixelated; }
+.entry h2 { margin: 0 0 0.5em 0; }
+.entry dl { display: grid; grid-template-columns: max-content auto; gap: 0.25em 1em; margin: 0; }
+.entry dt { font-weight: bold; }
+.entry dd { margin: 0; }
+.secret { font-family: monospace; font-size: 1.2em; letter-spacing: 0.05em; }
+.missing-qr { width: 180px; text-align: center; }
+"#;
+
+/// Builds a self-contained, printable HTML document with one block per entry.
+///
+/// Every block holds the entry QR code (as an inline PNG data URI), its name, issuer, account,
+/// the secret in grouped Base32 and the TOTP parameters, so the vault can be restored from paper.
+pub fn paper_backup_html(
+    vault_name: &str,
+    entries: &[ClockodeEntry],
+    exported_at: SystemTime,
+) -> String {
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>{} - Paper Backup</title>\n",
+        escape_html(vault_name)
+    ));
+    html.push_str(&format!("<style>{STYLESHEET}</style>\n</head>\n<body>\n"));
+
+    html.push_str(&format!(
+        "<header>\n<h1>{}</h1>\n<p>Clockode paper backup exported on {}</p>\n<p>{} {}. Keep this document somewhere safe, anyone holding it can generate your codes.</p>\n</header>\n",
+        escape_html(vault_name),
+        format_utc_datetime(exported_at),
+        entries.len(),
+        if entries.len() == 1 { "entry" } else { "entries" }
+    ));
+
+    for entry in entries {
+        html.push_str(&entry_block(entry));
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// HTML block of a single entry
+fn entry_block(entry: &ClockodeEntry) -> String {
+    let inputable = InputableClockodeEntry::from(entry.clone());
+
+    let qr = match inputable.get_qr_bytes() {
+        Ok(bytes) => format!(
+            "<img alt=\"QR code for {}\" src=\"data:image/png;base64,{}\">",
+            escape_html(&entry.name),
+            STANDARD.encode(bytes)
+        ),
+        Err(e) => {
+            warn!("Could not generate the QR code of {}: {}", entry.name, e);
+            String::from("<p class=\"missing-qr\">QR code unavailable, use the secret below</p>")
+        }
+    };
+
+    format!(
+        "<section class=\"entry\">\n{qr}\n<div>\n<h2>{name}</h2>\n<dl>\n<dt>Issuer</dt><dd>{issuer}</dd>\n<dt>Account</dt><dd>{account}</dd>\n<dt>Secret</dt><dd class=\"secret\">{secret}</dd>\n<dt>Algorithm</dt><dd>{algorithm}</dd>\n<dt>Digits</dt><dd>{digits}</dd>\n<dt>Period</dt><dd>{period}s</dd>\n</dl>\n</div>\n</section>\n",
+        name = escape_html(&entry.name),
+        issuer = escape_html(entry.totp.issuer.as_deref().unwrap_or("-")),
+        account = escape_html(&entry.totp.account_name),
+        secret = group_secret(&inputable.secret),
+        algorithm = entry.totp.algorithm,
+        digits = entry.totp.digits,
+        period = entry.totp.step,
+    )
+}
+
+/// Splits a Base32 secret in groups of four characters so it's easier to type back
+fn group_secret(secret: &str) -> String {
+    secret
+        .chars()
+        .filter(|c| *c != '=')
+        .collect::<Vec<_>>()
+        .chunks(4)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use totp_rs::{Algorithm, TOTP};
+
+    use super::*;
+
+    fn entry(name: &str, issuer: Option<&str>) -> ClockodeEntry {
+        ClockodeEntry {
+            id: None,
+            name: name.to_string(),
+            totp: TOTP::new_unchecked(
+                Algorithm::SHA1,
+                6,
+                1,
+                30,
+                b"12345678901234567890".to_vec(),
+                issuer.map(String::from),
+                String::from("alice@example.com"),
+            ),
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn escapes_names_and_issuers() {
+        let entries = [entry(
+            "<script>alert(\"hi\")</script>",
+            Some("Tom & Jerry's"),
+        )];
+        let html = paper_backup_html("<b>Vault</b>", &entries, SystemTime::UNIX_EPOCH);
+
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<b>"));
+        assert!(html.contains("<h2>&lt;script&gt;alert(&quot;hi&quot;)&lt;/script&gt;</h2>"));
+        assert!(html.contains("<dd>Tom &amp; Jerry&#39;s</dd>"));
+        assert!(html.contains("<h1>&lt;b&gt;Vault&lt;/b&gt;</h1>"));
+    }
+
+    #[test]
+    fn groups_secret_without_padding() {
+        assert_eq!(group_secret("JBSWY3DPEHPK3PXP"), "JBSW Y3DP EHPK 3PXP");
+        assert_eq!(group_secret("MFRGGZDF"), "MFRG GZDF");
+        assert_eq!(group_secret("MFRGG==="), "MFRG G");
+
+        let html = paper_backup_html("Vault", &[entry("github", None)], SystemTime::UNIX_EPOCH);
+        assert!(html.contains("<dd class=\"secret\">GEZD GNBV GY3T QOJQ GEZD GNBV GY3T QOJQ</dd>"));
+    }
+
+    #[test]
+    fn has_one_block_per_entry() {
+        let entries = [
+            entry("github", Some("GitHub")),
+            entry("gitlab", None),
+            entry("mail", Some("Mail")),
+        ];
+        let html = paper_backup_html("Vault", &entries, SystemTime::UNIX_EPOCH);
+
+        assert_eq!(html.matches("<section class=\"entry\">").count(), 3);
+        for entry in &entries {
+            let heading = format!("<h2>{}</h2>", entry.name);
+            assert_eq!(html.matches(&heading).count(), 1, "{}", entry.name);
+        }
+        assert!(html.contains("3 entries"));
+    }
+}
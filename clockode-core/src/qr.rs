@@ -0,0 +1,430 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use anywho::anywho;
+use image::{
+    DynamicImage, GrayImage, Luma,
+    imageops::{self, FilterType},
+};
+use std::path::{Path, PathBuf};
+use totp_rs::qrcodegen_image::qrcodegen::{QrCode, QrCodeEcc};
+use tracing::{debug, warn};
+
+/// Light modules kept around the code, the QR spec asks for at least 4
+const QUIET_ZONE: i32 = 4;
+
+/// Extensions of the image files QR codes can be read from
+pub const QR_IMAGE_EXTENSIONS: &[&str] = &["png", "jpeg", "jpg", "webp"];
+
+/// Longest side images are downscaled to before the first decoding attempts,
+/// huge screenshots are slow to scan and their noise confuses the finder pattern detection
+const MAX_DIMENSION: u32 = 1600;
+
+/// Images with a shorter side than this get upscaled, tiny codes have modules too small to sample
+const MIN_DIMENSION: u32 = 400;
+
+/// Whether the file at `path` looks like an image QR codes can be read from, based on its extension
+pub fn is_qr_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            QR_IMAGE_EXTENSIONS
+                .iter()
+                .any(|supported| extension.eq_ignore_ascii_case(supported))
+        })
+}
+
+/// Decodes every QR code found in the image at `path`.
+///
+/// Returns the payloads in the order they were detected, without duplicates,
+/// or an error if the image holds no readable QR code.
+pub fn read_qr_from_file(path: PathBuf) -> Result<Vec<String>, anywho::Error> {
+    let img = image::open(&path)?;
+    read_qr_from_image(&img)
+}
+
+/// Same as [`read_qr_from_file`] for an image that is already in memory
+pub fn read_qr_from_image(img: &DynamicImage) -> Result<Vec<String>, anywho::Error> {
+    let contents = decode_qr_codes(&img.to_luma8());
+
+    if contents.is_empty() {
+        Err(anywho!("No QR code found in image"))
+    } else {
+        Ok(contents)
+    }
+}
+
+/// Decodes every QR code in a grayscale image.
+///
+/// The image goes through a preprocessing pipeline that tries the image as is
+/// (downscaled if huge), inverted for light-on-dark codes, upscaled for tiny codes,
+/// adaptively thresholded for low-contrast photos, rotated and finally at full resolution.
+/// Every step runs, a code that only shows up after thresholding or inverting isn't lost
+/// because another one was readable in the original.
+pub fn decode_qr_codes(img: &GrayImage) -> Vec<String> {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    let is_large = width.max(height) > MAX_DIMENSION;
+    let base = if is_large {
+        downscale(img, MAX_DIMENSION)
+    } else {
+        img.clone()
+    };
+
+    let mut attempts: Vec<(&str, Box<dyn Fn() -> GrayImage + '_>)> = vec![
+        ("original", Box::new(|| base.clone())),
+        ("inverted", Box::new(|| inverted(&base))),
+    ];
+
+    if base.width().min(base.height()) < MIN_DIMENSION {
+        attempts.push(("upscaled", Box::new(|| upscale(&base, MIN_DIMENSION))));
+        attempts.push((
+            "upscaled inverted",
+            Box::new(|| inverted(&upscale(&base, MIN_DIMENSION))),
+        ));
+    }
+
+    attempts.push(("thresholded", Box::new(|| adaptive_threshold(&base))));
+    attempts.push((
+        "thresholded inverted",
+        Box::new(|| inverted(&adaptive_threshold(&base))),
+    ));
+    attempts.push((
+        "thresholded rotated",
+        Box::new(|| imageops::rotate90(&adaptive_threshold(&base))),
+    ));
+
+    if is_large {
+        // small codes in a huge screenshot may not survive the downscale
+        attempts.push(("full resolution", Box::new(|| img.clone())));
+        attempts.push((
+            "full resolution thresholded",
+            Box::new(|| adaptive_threshold(img)),
+        ));
+    }
+
+    let mut contents: Vec<String> = Vec::new();
+    for (name, attempt) in attempts {
+        for content in decode_grids(&attempt()) {
+            if !contents.contains(&content) {
+                debug!("QR code decoded on the {name} attempt");
+                contents.push(content);
+            }
+        }
+    }
+
+    contents
+}
+
+/// Runs `rqrr` over the image and returns every payload it could decode, without duplicates
+fn decode_grids(img: &GrayImage) -> Vec<String> {
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        img.width() as usize,
+        img.height() as usize,
+        |x, y| img.get_pixel(x as u32, y as u32)[0],
+    );
+
+    let mut contents: Vec<String> = Vec::new();
+    for grid in prepared.detect_grids() {
+        match grid.decode() {
+            Ok((_meta, content)) => {
+                if !contents.contains(&content) {
+                    contents.push(content);
+                }
+            }
+            Err(e) => warn!("Found a QR code that could not be decoded: {}", e),
+        }
+    }
+    contents
+}
+
+fn downscale(img: &GrayImage, max_dimension: u32) -> GrayImage {
+    let (width, height) = img.dimensions();
+    let ratio = f64::from(max_dimension) / f64::from(width.max(height));
+    let new_width = ((f64::from(width) * ratio) as u32).max(1);
+    let new_height = ((f64::from(height) * ratio) as u32).max(1);
+    imageops::resize(img, new_width, new_height, FilterType::Triangle)
+}
+
+fn upscale(img: &GrayImage, min_dimension: u32) -> GrayImage {
+    let (width, height) = img.dimensions();
+    let factor = min_dimension.div_ceil(width.min(height)).clamp(2, 8);
+    // nearest keeps the module edges sharp, smoothing them only makes sampling harder
+    imageops::resize(img, width * factor, height * factor, FilterType::Nearest)
+}
+
+fn inverted(img: &GrayImage) -> GrayImage {
+    let mut img = img.clone();
+    imageops::invert(&mut img);
+    img
+}
+
+/// Binarizes the image comparing every pixel against the mean of its neighbourhood,
+/// which copes with uneven lighting and low contrast much better than a global threshold
+fn adaptive_threshold(img: &GrayImage) -> GrayImage {
+    let (width, height) = img.dimensions();
+    let radius = (width.min(height) / 16).max(4);
+    let stride = (width + 1) as usize;
+
+    // summed-area table, so each neighbourhood mean costs four lookups
+    let mut integral = vec![0u64; stride * (height + 1) as usize];
+    for y in 0..height {
+        let mut row_sum = 0u64;
+        for x in 0..width {
+            row_sum += u64::from(img.get_pixel(x, y)[0]);
+            integral[(y + 1) as usize * stride + (x + 1) as usize] =
+                integral[y as usize * stride + (x + 1) as usize] + row_sum;
+        }
+    }
+
+    GrayImage::from_fn(width, height, |x, y| {
+        let x0 = x.saturating_sub(radius) as usize;
+        let y0 = y.saturating_sub(radius) as usize;
+        let x1 = (x + radius + 1).min(width) as usize;
+        let y1 = (y + radius + 1).min(height) as usize;
+
+        let sum = integral[y1 * stride + x1] + integral[y0 * stride + x0]
+            - integral[y0 * stride + x1]
+            - integral[y1 * stride + x0];
+        let count = ((x1 - x0) * (y1 - y0)) as u64;
+
+        // slightly below the mean, so flat areas don't turn into noise
+        if u64::from(img.get_pixel(x, y)[0]) * 100 * count < sum * 93 {
+            Luma([0])
+        } else {
+            Luma([255])
+        }
+    })
+}
+
+/// Renders `content` as a black on white QR code PNG of roughly `size` pixels per side.
+///
+/// Modules are always drawn at a whole number of pixels so the result stays sharp,
+/// which means the final image can be slightly smaller than `size`.
+pub fn render_qr_png(content: &str, size: u32) -> Result<Vec<u8>, anywho::Error> {
+    let qr = QrCode::encode_text(content, QrCodeEcc::Medium)
+        .map_err(|e| anywho!("Error generating the QR Code: {}", e))?;
+
+    let modules = (qr.size() + QUIET_ZONE * 2) as u32;
+    let scale = (size / modules).max(1);
+    let dimension = modules * scale;
+
+    let img = image::GrayImage::from_fn(dimension, dimension, |x, y| {
+        let module_x = (x / scale) as i32 - QUIET_ZONE;
+        let module_y = (y / scale) as i32 - QUIET_ZONE;
+        // get_module returns false (light) outside of the code, which draws the quiet zone
+        if qr.get_module(module_x, module_y) {
+            image::Luma([0])
+        } else {
+            image::Luma([255])
+        }
+    });
+
+    let mut bytes = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .map_err(|e| anywho!("Error encoding the QR Code: {}", e))?;
+
+    Ok(bytes)
+}
+
+/// Renders `content` as a black on white QR code SVG document.
+///
+/// Every module is one unit of the view box and the dark ones are drawn as a single path,
+/// so the code scales to any size without blurring or seams between modules.
+pub fn render_qr_svg(content: &str) -> Result<String, anywho::Error> {
+    let qr = QrCode::encode_text(content, QrCodeEcc::Medium)
+        .map_err(|e| anywho!("Error generating the QR Code: {}", e))?;
+
+    let modules = qr.size() + QUIET_ZONE * 2;
+
+    // one subpath per horizontal run of dark modules
+    let mut path = String::new();
+    for y in 0..qr.size() {
+        let mut x = 0;
+        while x < qr.size() {
+            if !qr.get_module(x, y) {
+                x += 1;
+                continue;
+            }
+
+            let start = x;
+            while x < qr.size() && qr.get_module(x, y) {
+                x += 1;
+            }
+            path.push_str(&format!(
+                "M{},{}h{}v1h-{}z",
+                start + QUIET_ZONE,
+                y + QUIET_ZONE,
+                x - start,
+                x - start
+            ));
+        }
+    }
+
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" viewBox=\"0 0 {modules} {modules}\" shape-rendering=\"crispEdges\">\n<rect width=\"100%\" height=\"100%\" fill=\"#FFFFFF\"/>\n<path d=\"{path}\" fill=\"#000000\"/>\n</svg>\n"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URI: &str =
+        "otpauth://totp/Clockode:alice@example.com?secret=JBSWY3DPEHPK3PXP&issuer=Clockode";
+
+    /// Clean black on white code, with `size` pixels per side at most
+    fn clean_fixture(size: u32) -> GrayImage {
+        let png = render_qr_png(URI, size).unwrap();
+        image::load_from_memory(&png).unwrap().to_luma8()
+    }
+
+    /// Rotates `img` by `degrees` around its center, filling the uncovered corners with white
+    fn rotated(img: &GrayImage, degrees: f64) -> GrayImage {
+        let (width, height) = img.dimensions();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (center_x, center_y) = (f64::from(width) / 2.0, f64::from(height) / 2.0);
+
+        GrayImage::from_fn(width, height, |x, y| {
+            let dx = f64::from(x) - center_x;
+            let dy = f64::from(y) - center_y;
+            let source_x = (cos * dx + sin * dy + center_x).round();
+            let source_y = (-sin * dx + cos * dy + center_y).round();
+            if source_x < 0.0
+                || source_y < 0.0
+                || source_x >= f64::from(width)
+                || source_y >= f64::from(height)
+            {
+                Luma([255])
+            } else {
+                *img.get_pixel(source_x as u32, source_y as u32)
+            }
+        })
+    }
+
+    fn assert_decodes(img: &GrayImage) {
+        assert_eq!(decode_qr_codes(img), vec![URI.to_string()]);
+    }
+
+    #[test]
+    fn decodes_clean_code() {
+        assert_decodes(&clean_fixture(300));
+    }
+
+    #[test]
+    fn decodes_low_contrast_code() {
+        let mut img = clean_fixture(300);
+        for pixel in img.pixels_mut() {
+            pixel[0] = if pixel[0] < 128 { 110 } else { 150 };
+        }
+        assert_decodes(&img);
+    }
+
+    #[test]
+    fn decodes_inverted_code() {
+        let mut img = clean_fixture(300);
+        imageops::invert(&mut img);
+        assert_decodes(&img);
+    }
+
+    #[test]
+    fn decodes_small_code() {
+        // one pixel per module
+        let img = clean_fixture(1);
+        assert!(img.width() < 64);
+        assert_decodes(&img);
+    }
+
+    #[test]
+    fn decodes_rotated_code() {
+        let img = clean_fixture(300);
+        assert_decodes(&imageops::rotate90(&img));
+        assert_decodes(&rotated(&img, 10.0));
+    }
+
+    #[test]
+    fn decodes_noisy_code() {
+        let mut img = clean_fixture(300);
+        // deterministic salt and pepper over ~3% of the pixels
+        let mut state: u32 = 0x2545_f491;
+        for pixel in img.pixels_mut() {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            match state >> 24 {
+                0..=3 => pixel[0] = 0,
+                4..=7 => pixel[0] = 255,
+                _ => {}
+            }
+        }
+        assert_decodes(&img);
+    }
+
+    #[test]
+    fn decodes_code_in_screenshot() {
+        // full HD desktop with a few window-like panels and a small code in one of them,
+        // like the ones the screenshot portal saves
+        let code = clean_fixture(180);
+        let mut screen = GrayImage::from_fn(1920, 1080, |x, y| match (x, y) {
+            (_, 0..40) => Luma([40]),
+            (0..300, _) => Luma([70]),
+            (1400.., 500..) => Luma([200]),
+            _ => Luma([235]),
+        });
+        imageops::replace(&mut screen, &code, 1500, 700);
+
+        let path =
+            std::env::temp_dir().join(format!("clockode-screenshot-{}.png", std::process::id()));
+        screen.save(&path).unwrap();
+        let result = read_qr_from_file(path.clone());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.unwrap(), vec![URI.to_string()]);
+    }
+
+    #[test]
+    fn decodes_every_code_of_an_image() {
+        // a regular code next to an inverted one, each is only found by one of the attempts
+        const OTHER_URI: &str =
+            "otpauth://totp/Clockode:bob@example.com?secret=KRSXG5CTMVRXEZLU&issuer=Clockode";
+        let png = render_qr_png(OTHER_URI, 300).unwrap();
+        let mut other = image::load_from_memory(&png).unwrap().to_luma8();
+        imageops::invert(&mut other);
+
+        // white left half, black right half
+        let mut img = GrayImage::from_pixel(660, 340, Luma([255]));
+        imageops::replace(&mut img, &GrayImage::new(330, 340), 330, 0);
+        imageops::replace(&mut img, &clean_fixture(300), 10, 20);
+        imageops::replace(&mut img, &other, 350, 20);
+
+        assert_eq!(
+            decode_qr_codes(&img),
+            vec![URI.to_string(), OTHER_URI.to_string()]
+        );
+    }
+
+    #[test]
+    fn decodes_fixture_images() {
+        // real files, see tests/fixtures: a dark mode app, a tilted photo in bad light,
+        // a 4K screenshot with a small code and a code at one pixel per module
+        for name in [
+            "inverted.png",
+            "low-contrast-photo.png",
+            "huge-screenshot.png",
+            "tiny-code.png",
+        ] {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(name);
+            let result = read_qr_from_file(path);
+            assert_eq!(result.ok(), Some(vec![URI.to_string()]), "{name}");
+        }
+    }
+
+    #[test]
+    fn finds_nothing_in_blank_image() {
+        let img = GrayImage::from_pixel(200, 200, Luma([255]));
+        assert!(decode_qr_codes(&img).is_empty());
+        assert!(read_qr_from_image(&DynamicImage::ImageLuma8(img)).is_err());
+    }
+}
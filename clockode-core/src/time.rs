@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+/// Calculates how many seconds remain until the next TOTP refresh
+/// based on standard TOTP timing (synchronized to Unix epoch)
+///
+/// # Arguments
+/// * `refresh_rate` - The TOTP refresh interval in seconds (typically 30)
+///
+/// # Returns
+/// The number of seconds until the next refresh occurs
+pub fn get_time_until_next_totp_refresh(refresh_rate: u64) -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let seconds: u64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    // Calculate remaining seconds until next window
+    // This will be synchronized with other TOTP apps since they
+    // all count from the same Unix epoch reference point
+    refresh_rate - (seconds % refresh_rate)
+}
+
+/// Formats the given [`SystemTime`] as a `YYYY-MM-DD HH:MM UTC` string
+///
+/// # Arguments
+/// * `time` - The point in time to format
+///
+/// # Returns
+/// The formatted date, or the unix epoch if `time` is before it
+pub fn format_utc_datetime(time: std::time::SystemTime) -> String {
+    let (year, month, day, seconds_of_day) = civil_from_system_time(time);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        (seconds_of_day % 3_600) / 60
+    )
+}
+
+/// Formats the given [`SystemTime`] as a compact `YYYYMMDD-HHMMSS` UTC stamp, safe to use in
+/// file names. Stamps sort in chronological order.
+///
+/// # Arguments
+/// * `time` - The point in time to format
+///
+/// # Returns
+/// The formatted stamp, or the unix epoch if `time` is before it
+pub fn format_utc_stamp(time: std::time::SystemTime) -> String {
+    let (year, month, day, seconds_of_day) = civil_from_system_time(time);
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        (seconds_of_day % 3_600) / 60,
+        seconds_of_day % 60
+    )
+}
+
+/// Splits the given [`SystemTime`] into a UTC civil date (year, month, day) and the seconds
+/// elapsed that day, times before the unix epoch are clamped to it
+fn civil_from_system_time(time: std::time::SystemTime) -> (i64, i64, i64, u64) {
+    let seconds = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let days = (seconds / 86_400) as i64;
+    let seconds_of_day = seconds % 86_400;
+
+    // Converts days since the unix epoch to a civil date
+    // See: https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day, seconds_of_day)
+}
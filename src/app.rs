@@ -9,17 +9,24 @@ use iced::{
 };
 use tracing::{error, info};
 
+use clockode_core::check_database;
+
 use crate::{
     APP_ID,
     app::{
-        core::check_database,
         screen::{HomePage, Screen, UnlockDatabase, create, homepage, unlock},
         widgets::Toast,
     },
     config::Config,
 };
 
-mod core;
+pub mod cli;
+#[cfg(unix)]
+mod dbus;
+#[cfg(unix)]
+pub mod instance;
+#[cfg(unix)]
+mod ipc;
 mod screen;
 mod utils;
 mod widgets;
@@ -29,6 +36,11 @@ pub struct Clockode {
     config: Arc<Mutex<Config>>,
     now: Instant,
     screen: Screen,
+    /// `otpauth://` URIs received while locked, opened in the entry form once unlocked
+    pending_uris: Vec<String>,
+    /// Handle to the D-Bus service once it is up, used to emit its signals
+    #[cfg(unix)]
+    dbus: Option<dbus::Service>,
 }
 
 #[derive(Debug, Clone)]
@@ -45,10 +57,16 @@ pub enum Message {
     UnlockDatabase(unlock::Message),
     /// Homepage [`Screen`] Messages
     HomePage(homepage::Message),
+    /// Events of the D-Bus service
+    #[cfg(unix)]
+    Dbus(dbus::Event),
+    /// A later launch handed something over to this instance
+    #[cfg(unix)]
+    Instance(instance::Command),
 }
 
 impl Clockode {
-    pub fn new() -> (Self, Task<Message>) {
+    pub fn new(uris: Vec<String>) -> (Self, Task<Message>) {
         info!("Starting app");
 
         let (screen, task) = Screen::from_database_check(check_database());
@@ -58,6 +76,9 @@ impl Clockode {
                 config: Arc::from(Mutex::new(Config::default())),
                 now: Instant::now(),
                 screen,
+                pending_uris: uris,
+                #[cfg(unix)]
+                dbus: None,
             },
             Task::perform(Config::load(APP_ID), Message::ConfigLoaded).chain(task),
         )
@@ -116,11 +137,14 @@ impl Clockode {
                     unlock::Action::Run(task) => task.map(Message::UnlockDatabase),
                     unlock::Action::AddToast(toast) => self.update(Message::AddToast(toast), now),
                     unlock::Action::OpenHomePage(database) => {
-                        let (homepage, task) =
-                            HomePage::new(Arc::new(*database), Arc::clone(&self.config));
+                        let (homepage, task) = HomePage::new(
+                            Arc::new(*database),
+                            Arc::clone(&self.config),
+                            std::mem::take(&mut self.pending_uris),
+                        );
 
                         self.screen = Screen::HomePage(homepage);
-                        task.map(Message::HomePage)
+                        Task::batch([task.map(Message::HomePage), self.notify_lock_state(false)])
                     }
                 }
             }
@@ -130,7 +154,7 @@ impl Clockode {
                     return Task::none();
                 };
 
-                match homepage.update(message, self.now) {
+                let task = match homepage.update(message, self.now) {
                     homepage::Action::None => Task::none(),
                     homepage::Action::Run(task) => task.map(Message::HomePage),
                     homepage::Action::AddToast(toast) => self.update(Message::AddToast(toast), now),
@@ -138,11 +162,127 @@ impl Clockode {
                         task.map(Message::HomePage),
                         self.update(Message::AddToast(toast), now),
                     ]),
+                    homepage::Action::Lock => self.lock(),
+                };
+
+                // turning the service off in the settings ends its subscription, the handle
+                // would otherwise keep the connection, and so the bus name, alive
+                #[cfg(unix)]
+                if !self.dbus_enabled() {
+                    self.dbus = None;
                 }
+
+                task
             }
+
+            #[cfg(unix)]
+            Message::Dbus(event) => match event {
+                dbus::Event::Ready(service) => {
+                    // it may have been turned off while connecting
+                    if self.dbus_enabled() {
+                        self.dbus = Some(service);
+                    }
+                    Task::none()
+                }
+                dbus::Event::Request(request) => match request {
+                    dbus::Request::Lock(responder) => {
+                        responder.respond(Ok(()));
+                        self.lock()
+                    }
+                    dbus::Request::IsLocked(responder) => {
+                        responder.respond(Ok(!matches!(self.screen, Screen::HomePage(_))));
+                        Task::none()
+                    }
+                    dbus::Request::ListEntries(_) | dbus::Request::CopyCode(..) => {
+                        if matches!(self.screen, Screen::HomePage(_)) {
+                            self.update(
+                                Message::HomePage(homepage::Message::DbusRequest(request)),
+                                now,
+                            )
+                        } else {
+                            request.reject("The vault is locked");
+                            Task::none()
+                        }
+                    }
+                },
+            },
+
+            #[cfg(unix)]
+            Message::Instance(command) => match command {
+                instance::Command::OpenUris { uris } => {
+                    Task::batch([self.open_uris(uris), Self::raise_window()])
+                }
+                instance::Command::Raise => Self::raise_window(),
+            },
         }
     }
 
+    /// Opens the given `otpauth://` URIs in the entry form, or keeps them until the vault is unlocked
+    #[cfg(unix)]
+    fn open_uris(&mut self, uris: Vec<String>) -> Task<Message> {
+        if matches!(self.screen, Screen::HomePage(_)) {
+            return self.update(
+                Message::HomePage(homepage::Message::OpenUris(uris)),
+                self.now,
+            );
+        }
+
+        self.pending_uris.extend(uris);
+        self.update(
+            Message::AddToast(Toast::success_toast(
+                "Unlock the vault to add the received entry",
+            )),
+            self.now,
+        )
+    }
+
+    /// Locks the vault, dropping the unlocked database and going back to the unlock screen
+    fn lock(&mut self) -> Task<Message> {
+        let Screen::HomePage(homepage) = &self.screen else {
+            return Task::none();
+        };
+
+        info!("Locking the vault");
+        let (unlock_database, task) = UnlockDatabase::new(homepage.database_path());
+        self.screen = Screen::UnlockDatabase(unlock_database);
+
+        Task::batch([
+            task.map(Message::UnlockDatabase),
+            self.notify_lock_state(true),
+        ])
+    }
+
+    /// Tells D-Bus listeners the vault was locked or unlocked
+    fn notify_lock_state(&self, locked: bool) -> Task<Message> {
+        #[cfg(unix)]
+        if let Some(service) = &self.dbus {
+            return Task::future(service.clone().emit_locked_changed(locked)).discard();
+        }
+
+        #[cfg(not(unix))]
+        let _ = locked;
+
+        Task::none()
+    }
+
+    /// Whether the D-Bus service should be running
+    #[cfg(unix)]
+    fn dbus_enabled(&self) -> bool {
+        self.config.lock().is_ok_and(|cfg| cfg.dbus_enabled)
+    }
+
+    /// Brings the window back from the taskbar and focuses it
+    #[cfg(unix)]
+    fn raise_window() -> Task<Message> {
+        iced::window::latest().then(|id| match id {
+            Some(id) => Task::batch([
+                iced::window::minimize(id, false),
+                iced::window::gain_focus(id),
+            ]),
+            None => Task::none(),
+        })
+    }
+
     pub fn view(&self) -> Element<'_, Message> {
         let content = match &self.screen {
             Screen::Error(error) => container(text(error)).center(Length::Fill).into(),
@@ -159,7 +299,7 @@ impl Clockode {
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
-        match &self.screen {
+        let screen_subscription = match &self.screen {
             Screen::Error(_) => Subscription::none(),
             Screen::CreateDatabase(create_database) => create_database
                 .subscription(self.now)
@@ -168,7 +308,20 @@ impl Clockode {
                 .subscription(self.now)
                 .map(Message::UnlockDatabase),
             Screen::HomePage(homepage) => homepage.subscription(self.now).map(Message::HomePage),
+        };
+
+        #[cfg(unix)]
+        let screen_subscription = Subscription::batch([
+            screen_subscription,
+            instance::listen().map(Message::Instance),
+        ]);
+
+        #[cfg(unix)]
+        if self.dbus_enabled() {
+            return Subscription::batch([screen_subscription, dbus::serve().map(Message::Dbus)]);
         }
+
+        screen_subscription
     }
 
     pub fn theme(&self) -> Theme {
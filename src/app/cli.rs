@@ -0,0 +1,487 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    io::{BufRead, IsTerminal, Write},
+    path::PathBuf,
+};
+
+use anywho::anywho;
+use clap::{Args, Parser, Subcommand};
+use secrecy::SecretString;
+use serde::Serialize;
+
+use clockode_core::{
+    ClockodeDatabase, ClockodeEntry, INCORRECT_PASSWORD, InputableClockodeEntry, WriteOutcome,
+    check_database, find_entries, get_time_until_next_totp_refresh, unlock_database,
+};
+
+use crate::{APP_ID, config::Config};
+
+/// TOTP client made with Iced, run without a subcommand to open the window
+#[derive(Debug, Parser)]
+#[command(name = "clockode", version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// otpauth:// URIs to open in the entry form, handed over to the running instance if any
+    #[arg(value_name = "URI")]
+    pub uris: Vec<String>,
+}
+
+impl Cli {
+    /// The `otpauth://` URIs among the positional arguments. Anything else, like files handed
+    /// over by a launcher or `otpauth-migration://` links (not supported), is logged and skipped
+    pub fn otpauth_uris(&self) -> Vec<String> {
+        self.uris
+            .iter()
+            .filter(|arg| {
+                let is_uri = arg.to_lowercase().starts_with("otpauth://");
+                if !is_uri {
+                    tracing::info!(
+                        "Ignoring the command line argument {arg:?}, not an otpauth URI"
+                    );
+                }
+                is_uri
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the current code of the entry matching the given name or issuer
+    Code {
+        query: String,
+        #[command(flatten)]
+        options: CommonOptions,
+    },
+    /// List the entries of the vault (secrets are never printed)
+    List {
+        #[command(flatten)]
+        options: CommonOptions,
+    },
+    /// Add an entry from an otpauth:// URI
+    Add {
+        /// The otpauth://totp/... URI of the entry
+        #[arg(long)]
+        uri: String,
+        /// Name of the entry, defaults to the account name of the URI
+        #[arg(long)]
+        name: Option<String>,
+        #[command(flatten)]
+        options: CommonOptions,
+    },
+    /// Import the entries of a text file with one otpauth:// URI per line
+    Import {
+        file: PathBuf,
+        #[command(flatten)]
+        options: CommonOptions,
+    },
+    /// Export every entry to a text file with one otpauth:// URI per line (NOT encrypted)
+    Export {
+        file: PathBuf,
+        #[command(flatten)]
+        options: CommonOptions,
+    },
+    /// Remove the entry matching the given name or issuer
+    Remove {
+        query: String,
+        /// Don't ask for confirmation
+        #[arg(long, short)]
+        yes: bool,
+        #[command(flatten)]
+        options: CommonOptions,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct CommonOptions {
+    /// Print the output (and errors) as JSON
+    #[arg(long)]
+    json: bool,
+    /// Read the password from the first line of the standard input
+    #[arg(long)]
+    password_stdin: bool,
+    /// Read the password from the first line of the given file descriptor
+    #[cfg(unix)]
+    #[arg(long, value_name = "FD", conflicts_with = "password_stdin")]
+    password_fd: Option<i32>,
+}
+
+/// Exit codes of the command line interface, scripts can rely on these values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success = 0,
+    /// Any error not covered by a more specific code
+    Failure = 1,
+    /// Invalid arguments (this is also what clap exits with)
+    Usage = 2,
+    /// There's no database yet, it has to be created from the app
+    NoDatabase = 3,
+    /// The password couldn't be read or is wrong
+    Authentication = 4,
+    /// No entry matches the query
+    NotFound = 5,
+    /// More than one entry matches the query
+    Ambiguous = 6,
+}
+
+struct CliError {
+    code: ExitCode,
+    message: String,
+}
+
+impl CliError {
+    fn new(code: ExitCode, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<anywho::Error> for CliError {
+    fn from(value: anywho::Error) -> Self {
+        Self::new(ExitCode::Failure, value)
+    }
+}
+
+/// Entry as printed by `list` and `code`, also used by the IPC socket responses
+#[derive(Serialize)]
+pub struct EntryOutput<'a> {
+    id: Option<String>,
+    name: &'a str,
+    issuer: Option<&'a str>,
+    account_name: &'a str,
+    algorithm: String,
+    digits: usize,
+    period: u64,
+}
+
+impl<'a> From<&'a ClockodeEntry> for EntryOutput<'a> {
+    fn from(entry: &'a ClockodeEntry) -> Self {
+        Self {
+            id: entry.id.map(|id| id.to_string()),
+            name: &entry.name,
+            issuer: entry.totp.issuer.as_deref(),
+            account_name: &entry.totp.account_name,
+            algorithm: entry.totp.algorithm.to_string(),
+            digits: entry.totp.digits,
+            period: entry.totp.step,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CodeOutput<'a> {
+    #[serde(flatten)]
+    pub entry: EntryOutput<'a>,
+    pub code: String,
+    pub remaining_seconds: u64,
+}
+
+#[derive(Serialize)]
+struct MessageOutput {
+    message: String,
+}
+
+#[derive(Serialize)]
+struct ErrorOutput<'a> {
+    error: &'a str,
+    exit_code: i32,
+}
+
+/// Runs the given subcommand and returns the process exit code
+pub fn run(command: Command) -> i32 {
+    let json = match &command {
+        Command::Code { options, .. }
+        | Command::List { options }
+        | Command::Add { options, .. }
+        | Command::Import { options, .. }
+        | Command::Export { options, .. }
+        | Command::Remove { options, .. } => options.json,
+    };
+
+    match smol::block_on(execute(command)) {
+        Ok(()) => ExitCode::Success as i32,
+        Err(err) => {
+            if json {
+                print_json(&ErrorOutput {
+                    error: &err.message,
+                    exit_code: err.code as i32,
+                });
+            } else {
+                eprintln!("clockode: {}", err.message);
+            }
+            err.code as i32
+        }
+    }
+}
+
+async fn execute(command: Command) -> Result<(), CliError> {
+    match command {
+        Command::Code { query, options } => {
+            let database = open_database(&options).await?;
+            let entries = database.list_entries().await?;
+            let entry = find_entry(&entries, &query)?;
+
+            let code = entry
+                .totp
+                .generate_current()
+                .map_err(|e| anywho!("Could not generate the code: {}", e))?;
+            let remaining_seconds = get_time_until_next_totp_refresh(entry.totp.step);
+
+            if options.json {
+                print_json(&CodeOutput {
+                    entry: EntryOutput::from(entry),
+                    code,
+                    remaining_seconds,
+                });
+            } else {
+                println!("{code}");
+            }
+            Ok(())
+        }
+        Command::List { options } => {
+            let database = open_database(&options).await?;
+            let entries = database.list_entries().await?;
+
+            if options.json {
+                print_json(&entries.iter().map(EntryOutput::from).collect::<Vec<_>>());
+            } else {
+                for entry in &entries {
+                    match &entry.totp.issuer {
+                        Some(issuer) => println!(
+                            "{}\t{}\t{}",
+                            entry.name, issuer, entry.totp.account_name
+                        ),
+                        None => println!("{}\t-\t{}", entry.name, entry.totp.account_name),
+                    }
+                }
+            }
+            Ok(())
+        }
+        Command::Add { uri, name, options } => {
+            let mut entry = InputableClockodeEntry::try_from(uri)
+                .map_err(|e| CliError::new(ExitCode::Usage, format!("Invalid URI: {e}")))?;
+            if let Some(name) = name {
+                entry.name = name;
+            } else if entry.name.trim().is_empty() {
+                entry.name = String::from("Default");
+            }
+
+            if !entry.valid() {
+                return Err(CliError::new(ExitCode::Usage, "Invalid TOTP Entity"));
+            }
+            let entry = ClockodeEntry::try_from(entry)?;
+            let name = entry.name.clone();
+
+            let database = open_database(&options).await?;
+            database.add_entry(entry).await?;
+
+            print_message(&options, format!("Added {name}"));
+            Ok(())
+        }
+        Command::Import { file, options } => {
+            let database = open_database(&options).await?;
+            let summary = database.import_content(file.clone()).await?;
+            if summary.imported == 0 {
+                return Err(CliError::new(
+                    ExitCode::Failure,
+                    format!("No otpauth:// URI could be read from {}", file.display()),
+                ));
+            }
+
+            print_message(&options, format!("{summary} from {}", file.display()));
+            Ok(())
+        }
+        Command::Export { file, options } => {
+            let database = open_database(&options).await?;
+            let entry_ids = database
+                .list_entries()
+                .await?
+                .into_iter()
+                .filter_map(|entry| entry.id)
+                .collect();
+            database.export_content(file.clone(), entry_ids).await?;
+
+            eprintln!(
+                "clockode: {} is NOT encrypted, keep it safe and delete it when done",
+                file.display()
+            );
+            print_message(&options, format!("Exported to {}", file.display()));
+            Ok(())
+        }
+        Command::Remove {
+            query,
+            yes,
+            options,
+        } => {
+            let database = open_database(&options).await?;
+            let entries = database.list_entries().await?;
+            let entry = find_entry(&entries, &query)?;
+
+            if !yes && !confirm(&format!("Remove {}?", entry.name))? {
+                return Err(CliError::new(ExitCode::Failure, "Cancelled"));
+            }
+
+            if let WriteOutcome::Conflict(_) = database.delete_entry(entry.clone()).await? {
+                return Err(CliError::new(
+                    ExitCode::Failure,
+                    format!(
+                        "{} was changed by another program meanwhile, try again",
+                        entry.name
+                    ),
+                ));
+            }
+
+            print_message(&options, format!("Removed {}", entry.name));
+            Ok(())
+        }
+    }
+}
+
+/// Unlocks the application database with the password read as the options say
+async fn open_database(options: &CommonOptions) -> Result<ClockodeDatabase, CliError> {
+    let path = check_database()?.ok_or_else(|| {
+        CliError::new(
+            ExitCode::NoDatabase,
+            "No database found, launch Clockode to create one",
+        )
+    })?;
+
+    let password = read_password(options)?;
+
+    let database = unlock_database(path, password, false).await.map_err(|e| {
+        if e.to_string() == INCORRECT_PASSWORD {
+            CliError::new(ExitCode::Authentication, e)
+        } else {
+            CliError::from(e)
+        }
+    })?;
+
+    // changes made from the command line are backed up like the ones made in the window
+    if let Ok(config) = Config::load(APP_ID).await {
+        database.set_backup_policy(config.backup_policy());
+    }
+
+    Ok(database)
+}
+
+fn read_password(options: &CommonOptions) -> Result<SecretString, CliError> {
+    let auth_error = |e: std::io::Error| {
+        CliError::new(
+            ExitCode::Authentication,
+            format!("Could not read the password: {e}"),
+        )
+    };
+
+    #[cfg(unix)]
+    if let Some(fd) = options.password_fd {
+        use std::os::fd::BorrowedFd;
+
+        if fd < 0 {
+            return Err(auth_error(std::io::Error::from(rustix::io::Errno::BADF)));
+        }
+
+        // SAFETY: the fd isn't negative and is only borrowed for this block, if the caller
+        // didn't actually hand it over open fcntl fails with EBADF before anything reads it
+        let borrowed = unsafe { BorrowedFd::borrow_raw(fd) };
+        rustix::io::fcntl_getfd(borrowed).map_err(|e| auth_error(e.into()))?;
+
+        // read through a duplicate, so the caller's descriptor stays open once we're done
+        let duplicate = rustix::io::dup(borrowed).map_err(|e| auth_error(e.into()))?;
+        let file = std::fs::File::from(duplicate);
+        return read_password_line(std::io::BufReader::new(file)).map_err(auth_error);
+    }
+
+    if options.password_stdin {
+        return read_password_line(std::io::stdin().lock()).map_err(auth_error);
+    }
+
+    if !std::io::stdin().is_terminal() {
+        return Err(CliError::new(
+            ExitCode::Authentication,
+            "No terminal to ask for the password, use --password-stdin or --password-fd",
+        ));
+    }
+
+    rpassword::prompt_password("Password: ")
+        .map(SecretString::from)
+        .map_err(auth_error)
+}
+
+fn read_password_line(mut reader: impl BufRead) -> Result<SecretString, std::io::Error> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let password = line.trim_end_matches(['\n', '\r']).to_string();
+    Ok(SecretString::from(password))
+}
+
+/// Finds the entry whose name or issuer matches `query`, exact (case insensitive) matches
+/// win over partial ones
+fn find_entry<'a>(
+    entries: &'a [ClockodeEntry],
+    query: &str,
+) -> Result<&'a ClockodeEntry, CliError> {
+    let matches = find_entries(entries, query);
+
+    match matches.as_slice() {
+        [] => Err(CliError::new(
+            ExitCode::NotFound,
+            format!("No entry matches \"{query}\""),
+        )),
+        [entry] => Ok(entry),
+        _ => Err(CliError::new(
+            ExitCode::Ambiguous,
+            format!(
+                "\"{query}\" matches more than one entry: {}",
+                matches
+                    .iter()
+                    .map(|entry| entry.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )),
+    }
+}
+
+/// Asks a yes/no question on the terminal, defaults to no
+fn confirm(question: &str) -> Result<bool, CliError> {
+    if !std::io::stdin().is_terminal() {
+        return Err(CliError::new(
+            ExitCode::Usage,
+            "No terminal to ask for confirmation, use --yes",
+        ));
+    }
+
+    eprint!("{question} [y/N] ");
+    std::io::stderr()
+        .flush()
+        .map_err(|e| CliError::new(ExitCode::Failure, e))?;
+
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .map_err(|e| CliError::new(ExitCode::Failure, e))?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn print_message(options: &CommonOptions, message: String) {
+    if options.json {
+        print_json(&MessageOutput { message });
+    } else {
+        println!("{message}");
+    }
+}
+
+fn print_json(value: &impl Serialize) {
+    match serde_json::to_string(value) {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("clockode: could not serialize the output: {e}"),
+    }
+}
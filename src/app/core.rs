@@ -1,11 +0,0 @@
-// SPDX-License-Identifier: GPL-3.0-only
-
-mod database;
-mod entry;
-
-pub use database::ClockodeDatabase;
-pub use database::check_database;
-pub use database::create_database;
-pub use database::unlock_database;
-
-pub use entry::ClockodeEntry;
@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Session D-Bus service so launchers and shell extensions can drive Clockode.
+//!
+//! It is opt-in (see the settings page). While enabled it owns the `dev.mariinkys.Clockode` name
+//! on the session bus and serves the `dev.mariinkys.Clockode` interface at `/dev/mariinkys/Clockode`:
+//!
+//! - `Lock()` locks the vault, going back to the unlock screen
+//! - `IsLocked() -> b`
+//! - `ListEntries() -> a(sss)` id, name and issuer of every entry, fails while locked
+//! - `CopyCode(s id)` copies the current code of the entry to the clipboard, fails while locked
+//! - `LockedChanged(b locked)` signal, emitted whenever the vault is locked or unlocked
+//!
+//! Any program of the session can call these, so `CopyCode` has to be allowed by the user the
+//! same way IPC requests are, its caller is told apart by the pid the bus daemon reports.
+//! Listing the entries gives away no secrets and is answered right away.
+//!
+//! The tests run the service on a private `dbus-daemon`, they are skipped if it isn't installed.
+
+use std::sync::{Arc, Mutex};
+
+use clockode_core::APP_ID;
+use futures::{SinkExt, Stream, channel::mpsc, channel::oneshot};
+use iced::Subscription;
+use tracing::{error, info, warn};
+use zbus::{fdo, message::Header, object_server::SignalEmitter};
+
+use crate::app::ipc::Client;
+
+/// Path the interface is served at
+const OBJECT_PATH: &str = "/dev/mariinkys/Clockode";
+
+/// Id, name and issuer (empty if none) of an entry
+pub type EntrySummary = (String, String, String);
+
+/// Where the answer to a [`Request`] goes, cloneable so it can travel inside messages
+#[derive(Debug)]
+pub struct Responder<T>(Arc<Mutex<Option<oneshot::Sender<Result<T, String>>>>>);
+
+impl<T> Clone for Responder<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Responder<T> {
+    /// Sends the answer back to the D-Bus caller, only the first answer is used
+    pub fn respond(&self, result: Result<T, String>) {
+        let sender = self.0.lock().ok().and_then(|mut sender| sender.take());
+        if let Some(sender) = sender {
+            // the caller may have timed out already, nothing to do then
+            let _ = sender.send(result);
+        }
+    }
+}
+
+/// A method call the app has to answer
+#[derive(Debug, Clone)]
+pub enum Request {
+    Lock(Responder<()>),
+    IsLocked(Responder<bool>),
+    ListEntries(Responder<Vec<EntrySummary>>),
+    /// Copy the code of the entry with the given id, asked by the given process
+    CopyCode(String, Client, Responder<()>),
+}
+
+impl Request {
+    /// Answers the request with an error
+    pub fn reject(&self, message: &str) {
+        let error = String::from(message);
+        match self {
+            Request::Lock(responder) => responder.respond(Err(error)),
+            Request::IsLocked(responder) => responder.respond(Err(error)),
+            Request::ListEntries(responder) => responder.respond(Err(error)),
+            Request::CopyCode(_, _, responder) => responder.respond(Err(error)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The service is up, the handle is needed to emit signals
+    Ready(Service),
+    /// A method was called
+    Request(Request),
+}
+
+/// Handle to the running service
+#[derive(Debug, Clone)]
+pub struct Service {
+    connection: zbus::Connection,
+}
+
+impl Service {
+    /// Emits the `LockedChanged` signal
+    pub async fn emit_locked_changed(self, locked: bool) {
+        let result = async {
+            let interface = self
+                .connection
+                .object_server()
+                .interface::<_, Interface>(OBJECT_PATH)
+                .await?;
+            Interface::locked_changed(interface.signal_emitter(), locked).await
+        }
+        .await;
+
+        if let Err(err) = result {
+            warn!("Could not emit the LockedChanged D-Bus signal: {err}");
+        }
+    }
+}
+
+/// Runs the service for as long as the subscription is alive
+pub fn serve() -> Subscription<Event> {
+    Subscription::run(service_stream)
+}
+
+fn service_stream() -> impl Stream<Item = Event> {
+    iced::stream::channel(16, async move |mut output: mpsc::Sender<Event>| {
+        let interface = Interface {
+            events: output.clone(),
+        };
+
+        let connection = match zbus::connection::Builder::session() {
+            Ok(builder) => connect(builder, interface).await,
+            Err(err) => Err(err),
+        };
+
+        match connection {
+            Ok(connection) => {
+                info!("Serving {APP_ID} on the session bus");
+                let _ = output.send(Event::Ready(Service { connection })).await;
+
+                // The name is owned for as long as the connection lives
+                std::future::pending::<()>().await;
+            }
+            Err(err) => error!("Could not start the D-Bus service: {err}"),
+        }
+    })
+}
+
+async fn connect(
+    builder: zbus::connection::Builder<'_>,
+    interface: Interface,
+) -> zbus::Result<zbus::Connection> {
+    builder
+        .name(APP_ID)?
+        .serve_at(OBJECT_PATH, interface)?
+        .build()
+        .await
+}
+
+struct Interface {
+    events: mpsc::Sender<Event>,
+}
+
+/// The process that sent the method call, asked to the bus daemon
+async fn caller(connection: &zbus::Connection, header: &Header<'_>) -> fdo::Result<Client> {
+    let sender = header
+        .sender()
+        .ok_or_else(|| fdo::Error::Failed(String::from("The caller is unknown")))?;
+
+    let pid = fdo::DBusProxy::new(connection)
+        .await?
+        .get_connection_unix_process_id(sender.clone().into())
+        .await?;
+
+    Ok(Client::from_pid(pid as i32))
+}
+
+impl Interface {
+    /// Hands the request over to the app and waits for its answer
+    async fn call<T>(&self, request: impl FnOnce(Responder<T>) -> Request) -> fdo::Result<T> {
+        let (sender, receiver) = oneshot::channel();
+        let responder = Responder(Arc::new(Mutex::new(Some(sender))));
+
+        self.events
+            .clone()
+            .send(Event::Request(request(responder)))
+            .await
+            .map_err(|_| fdo::Error::Failed(String::from("Clockode is shutting down")))?;
+
+        receiver
+            .await
+            .map_err(|_| fdo::Error::Failed(String::from("The request was dropped")))?
+            .map_err(fdo::Error::Failed)
+    }
+}
+
+#[zbus::interface(name = "dev.mariinkys.Clockode")]
+impl Interface {
+    async fn lock(&self) -> fdo::Result<()> {
+        self.call(Request::Lock).await
+    }
+
+    async fn is_locked(&self) -> fdo::Result<bool> {
+        self.call(Request::IsLocked).await
+    }
+
+    async fn list_entries(&self) -> fdo::Result<Vec<EntrySummary>> {
+        self.call(Request::ListEntries).await
+    }
+
+    async fn copy_code(
+        &self,
+        id: String,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &zbus::Connection,
+    ) -> fdo::Result<()> {
+        let client = caller(connection, &header).await?;
+        self.call(|responder| Request::CopyCode(id, client, responder))
+            .await
+    }
+
+    #[zbus(signal)]
+    async fn locked_changed(emitter: &SignalEmitter<'_>, locked: bool) -> zbus::Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+    };
+
+    use futures::StreamExt;
+
+    use super::*;
+
+    const INTERFACE: &str = "dev.mariinkys.Clockode";
+
+    /// A `dbus-daemon` of its own, so the tests never touch the desktop session bus
+    struct PrivateBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        /// Starts the daemon, `None` if it isn't installed or didn't start
+        fn start() -> Option<Self> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+
+            let mut address = String::new();
+            let read = daemon
+                .stdout
+                .take()
+                .map(|stdout| BufReader::new(stdout).read_line(&mut address));
+
+            let bus = Self {
+                daemon,
+                address: address.trim().to_string(),
+            };
+            (matches!(read, Some(Ok(_))) && !bus.address.is_empty()).then_some(bus)
+        }
+
+        fn connection(&self) -> zbus::Result<zbus::connection::Builder<'_>> {
+            zbus::connection::Builder::address(self.address.as_str())
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    async fn call<B>(
+        client: &zbus::Connection,
+        method: &str,
+        body: &B,
+    ) -> zbus::Result<zbus::Message>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+    {
+        client
+            .call_method(Some(APP_ID), OBJECT_PATH, Some(INTERFACE), method, body)
+            .await
+    }
+
+    #[tokio::test]
+    #[ignore = "needs dbus-daemon, run with `cargo test -- --ignored`"]
+    async fn serves_the_interface_on_a_private_bus() {
+        let bus = PrivateBus::start().expect("dbus-daemon should be installed and start");
+
+        let (events, mut requests) = mpsc::channel(16);
+        let _service = connect(bus.connection().unwrap(), Interface { events })
+            .await
+            .expect("the service should own its name on the private bus");
+        let client = bus.connection().unwrap().build().await.unwrap();
+
+        // stands in for the app, answering every request the service hands over
+        tokio::spawn(async move {
+            while let Some(event) = requests.next().await {
+                let Event::Request(request) = event else {
+                    continue;
+                };
+
+                match request {
+                    Request::Lock(responder) => responder.respond(Err(String::from("Nope"))),
+                    Request::IsLocked(responder) => responder.respond(Ok(true)),
+                    Request::ListEntries(responder) => responder.respond(Ok(vec![(
+                        String::from("1"),
+                        String::from("GitHub"),
+                        String::new(),
+                    )])),
+                    // the caller is this very test process
+                    Request::CopyCode(id, client, responder) => {
+                        if id == "1" && client.pid == std::process::id() as i32 {
+                            responder.respond(Ok(()));
+                        } else {
+                            responder.respond(Err(format!("Unexpected call from {client}")));
+                        }
+                    }
+                }
+            }
+        });
+
+        let locked: bool = call(&client, "IsLocked", &())
+            .await
+            .unwrap()
+            .body()
+            .deserialize()
+            .unwrap();
+        assert!(locked);
+
+        let entries: Vec<EntrySummary> = call(&client, "ListEntries", &())
+            .await
+            .unwrap()
+            .body()
+            .deserialize()
+            .unwrap();
+        assert_eq!(
+            entries,
+            vec![(String::from("1"), String::from("GitHub"), String::new())]
+        );
+
+        call(&client, "CopyCode", &("1",))
+            .await
+            .expect("CopyCode should carry the caller's pid");
+
+        assert!(matches!(
+            call(&client, "Lock", &()).await,
+            Err(zbus::Error::MethodError(..))
+        ));
+    }
+}
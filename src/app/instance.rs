@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Hands work over to an instance that is already running.
+//!
+//! Every instance listens on a socket in a private directory under `$XDG_RUNTIME_DIR`. A second
+//! launch, for example the browser opening an `otpauth://` link, connects to it, sends what it was
+//! asked to do as a JSON line and exits instead of opening another window. With the single
+//! instance mode enabled in the settings, a plain launch does the same and only raises the window.
+
+use std::{io::Write, os::unix::net::UnixStream as StdUnixStream};
+
+use anywho::anywho;
+use futures::{SinkExt, Stream, StreamExt, channel::mpsc};
+use iced::Subscription;
+use serde::{Deserialize, Serialize};
+use smol::{io::AsyncBufReadExt, io::BufReader, net::unix::UnixStream};
+use tracing::{error, info, warn};
+
+use crate::app::utils::{bind_private_socket, runtime_socket_path};
+
+/// Name of the socket file inside the runtime directory of the app
+const SOCKET_NAME: &str = "instance.sock";
+
+/// Something a second launch asks the running instance to do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Open the entry form filled with the given `otpauth://` URIs
+    OpenUris { uris: Vec<String> },
+    /// Bring the window to the front
+    Raise,
+}
+
+/// Sends the command to the running instance, fails if there is none
+pub fn forward(command: &Command) -> Result<(), anywho::Error> {
+    let path = runtime_socket_path(SOCKET_NAME)
+        .ok_or_else(|| anywho!("No runtime directory available"))?;
+
+    let mut stream = StdUnixStream::connect(&path)?;
+    let mut line = serde_json::to_string(command)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+
+    Ok(())
+}
+
+/// Listens for commands of later launches for as long as the subscription is alive
+pub fn listen() -> Subscription<Command> {
+    Subscription::run(serve)
+}
+
+fn serve() -> impl Stream<Item = Command> {
+    iced::stream::channel(16, async move |output| {
+        let Some(path) = runtime_socket_path(SOCKET_NAME) else {
+            warn!("No runtime directory ($XDG_RUNTIME_DIR) available, later launches will open new windows");
+            return;
+        };
+
+        let (listener, _socket) = match bind_private_socket(&path) {
+            Ok(bound) => bound,
+            Err(err) => {
+                error!("Could not listen for later launches: {err}");
+                return;
+            }
+        };
+        info!("Listening for later launches at {:?}", path);
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    smol::spawn(handle_connection(stream, output.clone())).detach();
+                }
+                Err(err) => warn!("Could not accept a connection of a later launch: {err}"),
+            }
+        }
+    })
+}
+
+async fn handle_connection(stream: UnixStream, mut output: mpsc::Sender<Command>) {
+    let mut lines = BufReader::new(stream).lines();
+
+    while let Some(Ok(line)) = lines.next().await {
+        match serde_json::from_str::<Command>(&line) {
+            Ok(command) => {
+                if output.send(command).await.is_err() {
+                    break;
+                }
+            }
+            Err(err) => warn!("Ignoring an invalid command of a later launch: {err}"),
+        }
+    }
+}
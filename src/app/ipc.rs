@@ -0,0 +1,440 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Local socket other programs can use to ask an unlocked instance for codes.
+//!
+//! It is opt-in (see the settings page) and only runs while the vault is unlocked. The socket
+//! lives in a private directory under `$XDG_RUNTIME_DIR` and only accepts processes of the same
+//! user, checked with the peer credentials of every connection. The user still has to allow
+//! each request, or allow a program always. A program is its executable plus the script it runs,
+//! interpreters running inline code can't be allowed always.
+//!
+//! The protocol is line delimited JSON, one request per line and one response line for each:
+//!
+//! ```text
+//! {"method":"list"}                       -> {"entries":[{"name":"GitHub",...}]}
+//! {"method":"code","entry":"github"}      -> {"name":"GitHub",...,"code":"123456","remaining_seconds":12}
+//! {"method":"remaining","entry":"github"} -> {"remaining_seconds":12}
+//! anything that fails                     -> {"error":"..."}
+//! ```
+//!
+//! Entries are looked up by name or issuer the same way the `code` subcommand does it.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anywho::anywho;
+use clockode_core::{ClockodeEntry, find_entries, get_time_until_next_totp_refresh};
+use futures::{SinkExt, Stream, StreamExt, channel::mpsc, channel::oneshot};
+use iced::Subscription;
+use serde::{Deserialize, Serialize};
+use smol::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::unix::UnixStream,
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    app::{
+        cli::{CodeOutput, EntryOutput},
+        utils::{bind_private_socket, runtime_socket_path},
+    },
+    config::TrustedProgram,
+};
+
+/// Name of the socket file inside the runtime directory of the app
+const SOCKET_NAME: &str = "ipc.sock";
+
+/// A request as sent by a client
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Request {
+    /// List the entries of the vault, without secrets or codes
+    List,
+    /// Current code of the entry matching the given name or issuer
+    Code { entry: String },
+    /// Seconds until the code of the entry matching the given name or issuer changes
+    Remaining { entry: String },
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Request::List => write!(f, "list your entries"),
+            Request::Code { entry } => write!(f, "get the code of \"{entry}\""),
+            Request::Remaining { entry } => {
+                write!(f, "see when the code of \"{entry}\" changes")
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ListOutput<'a> {
+    entries: Vec<EntryOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct RemainingOutput {
+    remaining_seconds: u64,
+}
+
+#[derive(Serialize)]
+struct ErrorOutput {
+    error: String,
+}
+
+/// Executables that run whatever code they're handed, trusting one of them alone would trust
+/// every script (or `-c` one-liner) run with it
+const INTERPRETERS: &[&str] = &[
+    "sh", "bash", "dash", "zsh", "ksh", "mksh", "fish", "nu", "python", "pypy", "perl", "ruby",
+    "node", "deno", "bun", "lua", "luajit", "php", "tclsh", "pwsh", "rscript", "julia", "java",
+];
+
+/// The process on the other end of a connection
+#[derive(Debug, Clone, PartialEq)]
+pub struct Client {
+    pub pid: i32,
+    /// Executable of the process. `None` if it can't be read.
+    pub exe: Option<PathBuf>,
+    /// Script file the executable runs, the first non-option argument when it names a file
+    pub script: Option<PathBuf>,
+}
+
+impl Client {
+    /// Reads the executable and script of the process with the given pid from `/proc`
+    #[cfg(target_os = "linux")]
+    pub fn from_pid(pid: i32) -> Self {
+        let args: Vec<String> = std::fs::read(format!("/proc/{pid}/cmdline"))
+            .map(|cmdline| {
+                cmdline
+                    .split(|byte| *byte == 0)
+                    .filter(|arg| !arg.is_empty())
+                    .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let cwd = std::fs::read_link(format!("/proc/{pid}/cwd")).ok();
+
+        Self {
+            pid,
+            exe: std::fs::read_link(format!("/proc/{pid}/exe")).ok(),
+            script: script_argument(&args, cwd.as_deref()),
+        }
+    }
+
+    /// Only the pid is known outside of Linux, such a client can't be allowed always
+    #[cfg(not(target_os = "linux"))]
+    pub fn from_pid(pid: i32) -> Self {
+        Self {
+            pid,
+            exe: None,
+            script: None,
+        }
+    }
+
+    /// What "allow always" remembers for this client, `None` when it can't be told apart from
+    /// other programs: its executable is unknown, or it's an interpreter not running a script
+    /// file (`python3 -c ...`, `sh -c ...`)
+    pub fn trusted_program(&self) -> Option<TrustedProgram> {
+        let exe = self.exe.clone()?;
+        if self.script.is_none() && is_interpreter(&exe) {
+            return None;
+        }
+
+        Some(TrustedProgram {
+            exe,
+            script: self.script.clone(),
+        })
+    }
+}
+
+impl fmt::Display for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.exe, &self.script) {
+            (Some(exe), Some(script)) => write!(
+                f,
+                "{} running {} (pid {})",
+                exe.display(),
+                script.display(),
+                self.pid
+            ),
+            (Some(exe), None) => write!(f, "{} (pid {})", exe.display(), self.pid),
+            (None, _) => write!(f, "process {}", self.pid),
+        }
+    }
+}
+
+/// Whether the executable at `exe` is a known interpreter, ignoring version suffixes
+/// (`python3.12`, `lua5.4`)
+fn is_interpreter(exe: &Path) -> bool {
+    exe.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| {
+            name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-')
+                .to_ascii_lowercase()
+        })
+        .is_some_and(|name| INTERPRETERS.contains(&name.as_str()))
+}
+
+/// Script file run by a process with the given command line: its first argument that isn't an
+/// option, as long as it names an existing file. Relative paths are resolved from `cwd`.
+#[cfg(target_os = "linux")]
+fn script_argument(args: &[String], cwd: Option<&Path>) -> Option<PathBuf> {
+    let argument = args.iter().skip(1).find(|arg| !arg.starts_with('-'))?;
+
+    let path = Path::new(argument);
+    let path = match cwd {
+        Some(cwd) if path.is_relative() => cwd.join(path),
+        _ => path.to_path_buf(),
+    };
+
+    if path.is_file() {
+        path.canonicalize().ok()
+    } else {
+        None
+    }
+}
+
+/// A request waiting for the user (or the trusted clients list) to decide on it
+#[derive(Debug, Clone)]
+pub struct IpcRequest {
+    pub client: Client,
+    pub request: Request,
+    reply: Arc<Mutex<Option<oneshot::Sender<String>>>>,
+}
+
+impl IpcRequest {
+    /// Answers the request using the given vault entries
+    pub fn answer(&self, entries: &[ClockodeEntry]) {
+        let response = match &self.request {
+            Request::List => to_json(&ListOutput {
+                entries: entries.iter().map(EntryOutput::from).collect(),
+            }),
+            Request::Code { entry } => match find_entry(entries, entry) {
+                Ok(entry) => match entry.totp.generate_current() {
+                    Ok(code) => to_json(&CodeOutput {
+                        entry: EntryOutput::from(entry),
+                        code,
+                        remaining_seconds: get_time_until_next_totp_refresh(entry.totp.step),
+                    }),
+                    Err(e) => error_response(format!("Could not generate the code: {e}")),
+                },
+                Err(message) => error_response(message),
+            },
+            Request::Remaining { entry } => match find_entry(entries, entry) {
+                Ok(entry) => to_json(&RemainingOutput {
+                    remaining_seconds: get_time_until_next_totp_refresh(entry.totp.step),
+                }),
+                Err(message) => error_response(message),
+            },
+        };
+
+        self.send(response);
+    }
+
+    /// Answers the request with an error
+    pub fn reject(&self, message: impl Into<String>) {
+        self.send(error_response(message));
+    }
+
+    fn send(&self, response: String) {
+        let sender = self.reply.lock().ok().and_then(|mut reply| reply.take());
+        if let Some(sender) = sender {
+            // the client may be gone already, nothing to do then
+            let _ = sender.send(response);
+        }
+    }
+}
+
+/// Serves the IPC socket for as long as the subscription is alive, emitting every request received
+pub fn listen() -> Subscription<IpcRequest> {
+    Subscription::run(serve)
+}
+
+/// Path of the socket, `None` when the platform has no runtime directory
+pub fn socket_path() -> Option<PathBuf> {
+    runtime_socket_path(SOCKET_NAME)
+}
+
+fn serve() -> impl Stream<Item = IpcRequest> {
+    iced::stream::channel(16, async move |output| {
+        let Some(path) = socket_path() else {
+            warn!("No runtime directory ($XDG_RUNTIME_DIR) available, not serving the IPC socket");
+            return;
+        };
+
+        let (listener, _socket) = match bind_private_socket(&path) {
+            Ok(bound) => bound,
+            Err(err) => {
+                error!("Could not serve the IPC socket: {err}");
+                return;
+            }
+        };
+        info!("Serving the IPC socket at {:?}", path);
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    smol::spawn(handle_connection(stream, output.clone())).detach();
+                }
+                Err(err) => warn!("Could not accept an IPC connection: {err}"),
+            }
+        }
+    })
+}
+
+async fn handle_connection(stream: UnixStream, mut output: mpsc::Sender<IpcRequest>) {
+    let client = match peer(&stream) {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("Rejected an IPC connection: {err}");
+            return;
+        }
+    };
+
+    let mut lines = BufReader::new(stream.clone()).lines();
+    let mut writer = stream;
+
+    while let Some(Ok(line)) = lines.next().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let (sender, receiver) = oneshot::channel();
+                let request = IpcRequest {
+                    client: client.clone(),
+                    request,
+                    reply: Arc::new(Mutex::new(Some(sender))),
+                };
+
+                if output.send(request).await.is_err() {
+                    error_response("Clockode is no longer serving requests")
+                } else {
+                    receiver
+                        .await
+                        .unwrap_or_else(|_| error_response("The request was dismissed"))
+                }
+            }
+            Err(err) => error_response(format!("Invalid request: {err}")),
+        };
+
+        if writer
+            .write_all(format!("{response}\n").as_bytes())
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Reads who is on the other end of the connection, refusing processes of other users
+#[cfg(target_os = "linux")]
+fn peer(stream: &UnixStream) -> Result<Client, anywho::Error> {
+    let credentials = rustix::net::sockopt::socket_peercred(stream)?;
+
+    if credentials.uid != rustix::process::getuid() {
+        return Err(anywho!(
+            "connection from another user (uid {})",
+            credentials.uid.as_raw()
+        ));
+    }
+
+    Ok(Client::from_pid(credentials.pid.as_raw_nonzero().get()))
+}
+
+/// Peer credentials are only read on Linux, elsewhere every connection is refused
+#[cfg(not(target_os = "linux"))]
+fn peer(_stream: &UnixStream) -> Result<Client, anywho::Error> {
+    Err(anywho!("peer credentials are not supported on this platform"))
+}
+
+fn find_entry<'a>(entries: &'a [ClockodeEntry], query: &str) -> Result<&'a ClockodeEntry, String> {
+    match find_entries(entries, query).as_slice() {
+        [] => Err(format!("No entry matches \"{query}\"")),
+        [entry] => Ok(entry),
+        _ => Err(format!("\"{query}\" matches more than one entry")),
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|e| error_response(e.to_string()))
+}
+
+fn error_response(message: impl Into<String>) -> String {
+    serde_json::to_string(&ErrorOutput {
+        error: message.into(),
+    })
+    .unwrap_or_else(|_| String::from(r#"{"error":"Unknown error"}"#))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(exe: &str, script: Option<&str>) -> Client {
+        Client {
+            pid: 1,
+            exe: Some(PathBuf::from(exe)),
+            script: script.map(PathBuf::from),
+        }
+    }
+
+    #[test]
+    fn interpreters_need_a_script_to_be_trusted() {
+        assert_eq!(client("/usr/bin/python3.12", None).trusted_program(), None);
+        assert_eq!(client("/usr/bin/bash", None).trusted_program(), None);
+        assert_eq!(
+            client("/usr/bin/python3", Some("/home/user/otp.py")).trusted_program(),
+            Some(TrustedProgram {
+                exe: PathBuf::from("/usr/bin/python3"),
+                script: Some(PathBuf::from("/home/user/otp.py")),
+            })
+        );
+    }
+
+    #[test]
+    fn other_programs_are_trusted_by_executable() {
+        assert_eq!(
+            client("/usr/bin/otp-launcher", None).trusted_program(),
+            Some(TrustedProgram {
+                exe: PathBuf::from("/usr/bin/otp-launcher"),
+                script: None,
+            })
+        );
+        assert_eq!(
+            Client {
+                pid: 1,
+                exe: None,
+                script: None
+            }
+            .trusted_program(),
+            None
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn finds_the_script_argument() {
+        let dir = std::env::temp_dir().join(format!("clockode-ipc-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("otp.py");
+        std::fs::write(&script, "").unwrap();
+        let script = script.canonicalize().unwrap();
+
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let found = [
+            script_argument(&args(&["python3", "-u", "otp.py", "github"]), Some(&dir)),
+            script_argument(&args(&["python3", "-c", "print(1)"]), Some(&dir)),
+            script_argument(&args(&["python3", "-m", "otp"]), Some(&dir)),
+        ];
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found, [Some(script), None, None]);
+    }
+}
@@ -15,9 +15,11 @@ use iced::{
     },
 };
 
+use clockode_core::create_database;
+
 use crate::{
     APP_ICON,
-    app::{core::create_database, utils::style, widgets::Toast},
+    app::{utils::style, widgets::Toast},
 };
 
 pub struct CreateDatabase {
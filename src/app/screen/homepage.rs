@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use std::{
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -8,25 +9,128 @@ use std::{
 use iced::{
     Alignment, Element,
     Length::{self},
-    Subscription, Task, clipboard,
+    Subscription, Task, clipboard, event,
     time::Instant,
-    widget::{Column, button, column, container, row, scrollable, space, text},
+    widget::{Column, button, column, container, row, scrollable, space, text, text_input},
 };
 use tracing::{error, info};
 
+use clockode_core::{
+    Backup, ClockodeDatabase, ClockodeEntry, ImportSummary, MigrationCodes, WriteOutcome,
+    get_time_until_next_totp_refresh, is_qr_image, shared_backups_dir, unlock_database,
+};
+
+#[cfg(unix)]
+use crate::{
+    APP_ID,
+    app::{dbus, ipc},
+};
 use crate::{
     app::{
-        core::{ClockodeDatabase, ClockodeEntry}, utils::{get_time_until_next_totp_refresh, style, watch_database}, widgets::{Toast, dot},
-    }, config::Config, icons,
+        utils::{style, watch_database},
+        widgets::{Toast, dot},
+    },
+    config::{Config, SharedVault},
+    icons,
 };
 
+mod backups;
+mod conflict;
+mod export;
+mod migration;
 mod settings;
 mod upsert;
 
+/// Requests of other programs that can wait for an answer at once, later ones are refused
+/// right away
+#[cfg(unix)]
+const MAX_ACCESS_PROMPTS: usize = 8;
+
 pub struct HomePage {
     config: Arc<Mutex<Config>>,
     database: Arc<ClockodeDatabase>,
     state: State,
+    /// `otpauth://` URIs waiting for the home screen to be shown to open them in the entry form
+    pending_uris: Vec<String>,
+    /// Vaults shown next to the personal one, in the order of the config
+    shared_vaults: Vec<MountedVault>,
+    /// Text the entries of every unlocked vault are filtered by
+    search: String,
+    /// Shared vault the open [`UpsertPage`] or [`ConflictPage`] writes to, `None` for the personal one
+    editing: Option<PathBuf>,
+    /// Requests of other programs waiting for the user to allow or deny them, the first one is
+    /// the one shown
+    #[cfg(unix)]
+    access_prompts: std::collections::VecDeque<AccessRequest>,
+}
+
+/// A request of another program that has to be allowed by the user
+#[cfg(unix)]
+enum AccessRequest {
+    Ipc(ipc::IpcRequest),
+    /// D-Bus `CopyCode` call for the given entry
+    DbusCopyCode(ClockodeEntry, ipc::Client, dbus::Responder<()>),
+}
+
+#[cfg(unix)]
+impl AccessRequest {
+    fn client(&self) -> &ipc::Client {
+        match self {
+            AccessRequest::Ipc(request) => &request.client,
+            AccessRequest::DbusCopyCode(_, client, _) => client,
+        }
+    }
+
+    /// What the program wants to do, to complete "<program> wants to ..."
+    fn description(&self) -> String {
+        match self {
+            AccessRequest::Ipc(request) => request.request.to_string(),
+            AccessRequest::DbusCopyCode(entry, ..) => {
+                format!("copy the code of \"{}\" (D-Bus)", entry.name)
+            }
+        }
+    }
+
+    fn reject(&self, message: &str) {
+        match self {
+            AccessRequest::Ipc(request) => request.reject(message),
+            AccessRequest::DbusCopyCode(.., responder) => responder.respond(Err(message.into())),
+        }
+    }
+}
+
+/// A [`SharedVault`] shown on the home screen
+struct MountedVault {
+    vault: SharedVault,
+    state: MountState,
+}
+
+enum MountState {
+    /// Waiting for its password, `unlocking` while it's being checked
+    Locked { password: String, unlocking: bool },
+    Unlocked {
+        database: Arc<ClockodeDatabase>,
+        entries: Vec<ClockodeEntry>,
+    },
+}
+
+impl MountedVault {
+    fn new(vault: SharedVault) -> Self {
+        Self {
+            vault,
+            state: MountState::Locked {
+                password: String::new(),
+                unlocking: false,
+            },
+        }
+    }
+
+    fn database(&self) -> Option<&Arc<ClockodeDatabase>> {
+        match &self.state {
+            MountState::Unlocked { database, .. } => Some(database),
+            MountState::Locked { .. } => None,
+        }
+    }
 }
 
 pub enum State {
@@ -38,6 +142,10 @@ pub enum SubScreen {
     Home { entries: Vec<ClockodeEntry> },
     UpsertPage(upsert::UpsertPage),
     SettingsPage(settings::SettingsPage),
+    MigrationPage(migration::MigrationPage),
+    ExportPage(export::ExportPage),
+    ConflictPage(conflict::ConflictPage),
+    BackupsPage(backups::BackupsPage),
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +154,8 @@ pub enum Message {
     CopyToClipboard(String),
     /// Callback after attempting to copy something to the clipboard
     ClipboardResult(Result<(), iced::clipboard::Error>),
+    /// Ask to lock the vault
+    Lock,
     /// Ask to load the [`ClockodeEntry`]s to list on the page
     LoadEntries,
     /// Callback after asking to load [`ClockodeEntry`]s, set's the entries on the state if Ok
@@ -55,18 +165,96 @@ pub enum Message {
     UpsertPage(upsert::Message),
     /// Ask to open the [`ClockodeEntry`]  [`UpsertPage`]
     OpenUpsertPage(Option<ClockodeEntry>),
+    /// Ask to open the [`UpsertPage`] of an entry of the shared vault at the given path
+    OpenSharedUpsertPage(PathBuf, ClockodeEntry),
     /// Callback after upserting a [`ClockodeEntry`]
     EntryUpserted(Result<(), anywho::Error>),
+    /// Callback after updating or deleting a [`ClockodeEntry`], opens the [`ConflictPage`] if it
+    /// was changed on disk too
+    EntryWritten(Result<WriteOutcome, anywho::Error>),
+    /// Callback after importing a file of `otpauth://` URIs
+    ContentImported(Result<ImportSummary, anywho::Error>),
+
+    /// Messages of the [`ConflictPage`]
+    ConflictPage(conflict::Message),
+    /// Callback after writing an unencrypted export, with the path of the written file
+    PlaintextExported(Result<PathBuf, anywho::Error>),
+    /// Callback after exporting the entries to a new KeePass vault, with the path of the vault
+    VaultExported(Result<PathBuf, anywho::Error>),
 
     /// Messages of the [`SettingsPage`]
     SettingsPage(settings::Message),
     /// Ask to open the [`SettingsPage`]
     OpenSettingsPage,
 
+    /// Messages of the [`ExportPage`]
+    ExportPage(export::Message),
+    /// Callback after loading the entries to pick from, opens the [`ExportPage`] if Ok
+    ExportEntriesLoaded(export::ExportFormat, Result<Vec<ClockodeEntry>, anywho::Error>),
+
+    /// Messages of the [`BackupsPage`]
+    BackupsPage(backups::Message),
+    /// Callback after listing the backups of the vault, opens the [`BackupsPage`] if Ok
+    BackupsLoaded(Result<Vec<Backup>, anywho::Error>),
+
+    /// Messages of the [`MigrationPage`]
+    MigrationPage(migration::Message),
+    /// Callback after encoding the entries as migration URIs, opens the [`MigrationPage`] if Ok
+    MigrationCodesLoaded(Result<MigrationCodes, anywho::Error>),
+
     /// Makes iced rerun the view to refresh and tick the timers, runs every second on a subscription
     RefreshCodes,
     /// The database changed (watcher)
     DatabaseChangedOnDisk,
+    /// The search text changed
+    SearchChanged(String),
+
+    /// The password typed for the shared vault at the given path changed
+    SharedVaultPasswordChanged(PathBuf, String),
+    /// Unlock the shared vault at the given path with the typed password
+    UnlockSharedVault(PathBuf),
+    /// Callback after unlocking a shared vault, loads its entries if Ok
+    SharedVaultUnlocked(PathBuf, Box<Result<ClockodeDatabase, anywho::Error>>),
+    /// Callback after loading the entries of a shared vault
+    SharedEntriesLoaded(PathBuf, Result<Vec<ClockodeEntry>, anywho::Error>),
+    /// The shared vault at the given path changed (watcher)
+    SharedVaultChangedOnDisk(PathBuf),
+    /// Lock the shared vault at the given path again
+    LockSharedVault(PathBuf),
+    /// A file was dropped on the window, images open the [`UpsertPage`] with it, anything else is imported
+    FileDropped(PathBuf),
+    /// Open the [`UpsertPage`] filled with the given `otpauth://` URIs (command line or link handler)
+    OpenUris(Vec<String>),
+
+    /// A program asked for something over the IPC socket
+    #[cfg(unix)]
+    IpcRequest(ipc::IpcRequest),
+    /// The user answered the IPC or D-Bus request currently shown
+    #[cfg(unix)]
+    AccessDecision(AccessDecision),
+    /// Callback after loading the entries to answer an allowed IPC request with
+    #[cfg(unix)]
+    IpcEntriesLoaded(ipc::IpcRequest, Result<Vec<ClockodeEntry>, anywho::Error>),
+    /// Callback after saving the config with a newly trusted program
+    #[cfg(unix)]
+    IpcConfigSaved(Result<(), anywho::Error>),
+
+    /// A D-Bus method that needs the unlocked vault was called
+    #[cfg(unix)]
+    DbusRequest(dbus::Request),
+    /// Callback after loading the entries to answer a D-Bus method call with
+    #[cfg(unix)]
+    DbusEntriesLoaded(dbus::Request, Result<Vec<ClockodeEntry>, anywho::Error>),
+}
+
+/// What the user decided about an IPC or D-Bus request
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy)]
+pub enum AccessDecision {
+    Deny,
+    AllowOnce,
+    /// Allow this one and every future request of the same program
+    AllowAlways,
 }
 
 pub enum Action {
@@ -78,21 +266,34 @@ pub enum Action {
     AddToast(Toast),
     /// Ask parent to run an [`iced::Task`] and add a [`Toast`] to show
     RunAndToast(Task<Message>, Toast),
+    /// Ask parent to lock the vault
+    Lock,
 }
 
 impl HomePage {
     pub fn new(
         database: Arc<ClockodeDatabase>,
         config: Arc<Mutex<Config>>,
+        uris: Vec<String>,
     ) -> (Self, Task<Message>) {
         let db_clone = Arc::clone(&database);
 
+        let mut homepage = Self {
+            config,
+            database,
+            state: State::Loading,
+            pending_uris: uris,
+            shared_vaults: Vec::new(),
+            search: String::new(),
+            editing: None,
+            #[cfg(unix)]
+            access_prompts: std::collections::VecDeque::new(),
+        };
+        homepage.sync_shared_vaults();
+        homepage.apply_backup_policy();
+
         (
-            Self {
-                config,
-                database,
-                state: State::Loading,
-            },
+            homepage,
             Task::perform(
                 async move { db_clone.list_entries().await },
                 Message::EntriesLoaded,
@@ -100,15 +301,24 @@ impl HomePage {
         )
     }
 
+    /// Path of the unlocked database file
+    pub fn database_path(&self) -> PathBuf {
+        (*self.database.path()).clone()
+    }
+
     pub fn view(&self, now: Instant) -> iced::Element<'_, Message> {
         let content: Element<Message> = match &self.state {
             State::Loading => text("Loading...").into(),
             State::Ready { subscreen } => match subscreen {
                 SubScreen::Home { entries } => {
-                    let header = header_view(entries.len());
-                    let content = content_view(entries);
+                    let read_only = self.database.is_read_only();
+                    let header = header_view(entries.len(), read_only);
+                    let search = (!entries.is_empty() || !self.shared_vaults.is_empty())
+                        .then(|| search_view(&self.search));
+                    let content =
+                        content_view(entries, read_only, &self.shared_vaults, &self.search);
 
-                    container(column![header, content])
+                    container(column![header].extend(search).push(content))
                         .padding(5.)
                         .width(Length::Fill)
                         .height(Length::Fill)
@@ -120,9 +330,31 @@ impl HomePage {
                 SubScreen::SettingsPage(settings_page) => {
                     settings_page.view(now).map(Message::SettingsPage)
                 }
+                SubScreen::MigrationPage(migration_page) => {
+                    migration_page.view(now).map(Message::MigrationPage)
+                }
+                SubScreen::ExportPage(export_page) => {
+                    export_page.view(now).map(Message::ExportPage)
+                }
+                SubScreen::ConflictPage(conflict_page) => {
+                    conflict_page.view(now).map(Message::ConflictPage)
+                }
+                SubScreen::BackupsPage(backups_page) => {
+                    backups_page.view(now).map(Message::BackupsPage)
+                }
             },
         };
 
+        #[cfg(unix)]
+        let content = match self.access_prompts.front() {
+            Some(request) => iced::widget::stack![
+                content,
+                access_prompt_view(request, self.access_prompts.len())
+            ]
+            .into(),
+            None => content,
+        };
+
         container(content).center(Length::Fill).into()
     }
 
@@ -138,13 +370,24 @@ impl HomePage {
                     Action::None
                 }
             },
+            Message::Lock => Action::Lock,
             Message::LoadEntries => {
                 self.state = State::Loading;
+                self.sync_shared_vaults();
+                self.apply_backup_policy();
 
                 let db_clone = Arc::clone(&self.database);
-                Action::Run(Task::perform(
-                    async move { db_clone.list_entries().await },
-                    Message::EntriesLoaded,
+                let shared_reloads = self
+                    .shared_vaults
+                    .iter()
+                    .filter_map(|mounted| Some(load_shared_entries(mounted.database()?)));
+
+                Action::Run(Task::batch(
+                    std::iter::once(Task::perform(
+                        async move { db_clone.list_entries().await },
+                        Message::EntriesLoaded,
+                    ))
+                    .chain(shared_reloads),
                 ))
             }
             Message::EntriesLoaded(result) => match result {
@@ -152,7 +395,12 @@ impl HomePage {
                     self.state = State::Ready {
                         subscreen: SubScreen::Home { entries },
                     };
-                    Action::None
+
+                    if self.pending_uris.is_empty() {
+                        Action::None
+                    } else {
+                        self.update(Message::OpenUris(Vec::new()), now)
+                    }
                 }
                 Err(err) => {
                     error!("{err}");
@@ -174,27 +422,22 @@ impl HomePage {
                     upsert::Action::Back => self.update(Message::LoadEntries, now),
                     upsert::Action::Run(task) => Action::Run(task.map(Message::UpsertPage)),
                     upsert::Action::AddToast(toast) => Action::AddToast(toast),
-                    upsert::Action::UpdateEntry(clockode_entry) => {
-                        let db_clone = Arc::clone(&self.database);
-                        Action::Run(Task::perform(
-                            async move { db_clone.update_entry(clockode_entry).await },
-                            Message::EntryUpserted,
-                        ))
-                    }
-                    upsert::Action::CreateEntry(clockode_entry) => {
-                        let db_clone = Arc::clone(&self.database);
-                        Action::Run(Task::perform(
-                            async move { db_clone.add_entry(clockode_entry).await },
-                            Message::EntryUpserted,
-                        ))
-                    }
-                    upsert::Action::DeleteEntry(uuid) => {
-                        let db_clone = Arc::clone(&self.database);
-                        Action::Run(Task::perform(
-                            async move { db_clone.delete_entry(uuid).await },
-                            Message::EntryUpserted,
-                        ))
-                    }
+                    upsert::Action::UpdateEntry(clockode_entry, base) => self.write_editing(
+                        |db| async move { db.update_entry(clockode_entry, base).await },
+                        Message::EntryWritten,
+                    ),
+                    upsert::Action::CreateEntry(clockode_entry) => self.write_editing(
+                        |db| async move { db.add_entry(clockode_entry).await },
+                        Message::EntryUpserted,
+                    ),
+                    upsert::Action::CreateEntries(clockode_entries) => self.write_editing(
+                        |db| async move { db.add_entries(clockode_entries).await },
+                        Message::EntryUpserted,
+                    ),
+                    upsert::Action::DeleteEntry(clockode_entry) => self.write_editing(
+                        |db| async move { db.delete_entry(clockode_entry).await },
+                        Message::EntryWritten,
+                    ),
                 }
             }
             Message::OpenUpsertPage(entry) => {
@@ -204,6 +447,17 @@ impl HomePage {
 
                 let (upsert_page, task) = upsert::UpsertPage::new(entry);
                 *subscreen = SubScreen::UpsertPage(upsert_page);
+                self.editing = None;
+                Action::Run(task.map(Message::UpsertPage))
+            }
+            Message::OpenSharedUpsertPage(path, entry) => {
+                let State::Ready { subscreen, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                let (upsert_page, task) = upsert::UpsertPage::new(Some(entry));
+                *subscreen = SubScreen::UpsertPage(upsert_page);
+                self.editing = Some(path);
                 Action::Run(task.map(Message::UpsertPage))
             }
             Message::EntryUpserted(result) => match result {
@@ -222,6 +476,87 @@ impl HomePage {
                 }
             },
 
+            Message::EntryWritten(result) => match result {
+                Ok(WriteOutcome::Written) => self.update(Message::LoadEntries, now),
+                Ok(WriteOutcome::Conflict(conflict)) => {
+                    let State::Ready { subscreen } = &mut self.state else {
+                        return Action::None;
+                    };
+
+                    let (conflict_page, task) = conflict::ConflictPage::new(conflict);
+                    *subscreen = SubScreen::ConflictPage(conflict_page);
+                    Action::Run(task.map(Message::ConflictPage))
+                }
+                Err(err) => self.update(Message::EntryUpserted(Err(err)), now),
+            },
+
+            Message::ContentImported(result) => match result {
+                Ok(summary) if summary.imported == 0 => self.update(
+                    Message::EntryUpserted(Err(anywho::anywho!(
+                        "No otpauth:// URI could be read from the file"
+                    ))),
+                    now,
+                ),
+                // tell how many lines were left out, the entries are reloaded either way
+                Ok(summary) => match self.update(Message::LoadEntries, now) {
+                    Action::Run(task) if summary.skipped > 0 => {
+                        Action::RunAndToast(task, Toast::warning_toast(summary))
+                    }
+                    action => action,
+                },
+                Err(err) => self.update(Message::EntryUpserted(Err(err)), now),
+            },
+
+            Message::ConflictPage(message) => {
+                let State::Ready { subscreen } = &mut self.state else {
+                    return Action::None;
+                };
+
+                let SubScreen::ConflictPage(conflict_page) = subscreen else {
+                    return Action::None;
+                };
+
+                match conflict_page.update(message, now) {
+                    conflict::Action::Back => self.update(Message::LoadEntries, now),
+                    conflict::Action::Resolve(conflict, resolution) => self.write_editing(
+                        |db| async move { db.resolve_conflict(conflict, resolution).await },
+                        Message::EntryUpserted,
+                    ),
+                }
+            }
+
+            Message::PlaintextExported(result) => match result {
+                Ok(path) => {
+                    self.state = State::Loading;
+                    let db_clone = Arc::clone(&self.database);
+                    Action::RunAndToast(
+                        Task::perform(
+                            async move { db_clone.list_entries().await },
+                            Message::EntriesLoaded,
+                        ),
+                        Toast::warning_toast(format!(
+                            "Exported to {}. This file is NOT encrypted, keep it safe and delete it when done.",
+                            path.display()
+                        )),
+                    )
+                }
+                Err(err) => self.update(Message::EntryUpserted(Err(err)), now),
+            },
+            Message::VaultExported(result) => match result {
+                Ok(path) => {
+                    self.state = State::Loading;
+                    let db_clone = Arc::clone(&self.database);
+                    Action::RunAndToast(
+                        Task::perform(
+                            async move { db_clone.list_entries().await },
+                            Message::EntriesLoaded,
+                        ),
+                        Toast::success_toast(format!("Exported to {}", path.display())),
+                    )
+                }
+                Err(err) => self.update(Message::EntryUpserted(Err(err)), now),
+            },
+
             Message::SettingsPage(message) => {
                 let State::Ready { subscreen } = &mut self.state else {
                     return Action::None;
@@ -240,14 +575,21 @@ impl HomePage {
                         let db_clone = Arc::clone(&self.database);
                         Action::Run(Task::perform(
                             async move { db_clone.import_content(path_buf).await },
-                            Message::EntryUpserted,
+                            Message::ContentImported,
                         ))
                     }
-                    settings::Action::ExportContent(path_buf) => {
+                    settings::Action::OpenExport(format) => {
                         let db_clone = Arc::clone(&self.database);
                         Action::Run(Task::perform(
-                            async move { db_clone.export_content(path_buf).await },
-                            Message::EntryUpserted,
+                            async move { db_clone.list_entries().await },
+                            move |result| Message::ExportEntriesLoaded(format, result),
+                        ))
+                    }
+                    settings::Action::OpenBackups => {
+                        let db_clone = Arc::clone(&self.database);
+                        Action::Run(Task::perform(
+                            async move { db_clone.list_backups().await },
+                            Message::BackupsLoaded,
                         ))
                     }
                 }
@@ -257,11 +599,200 @@ impl HomePage {
                     return Action::None;
                 };
 
-                let (settings_page, task) = settings::SettingsPage::new(Arc::clone(&self.config));
+                let (settings_page, task) = settings::SettingsPage::new(
+                    Arc::clone(&self.config),
+                    self.database.is_read_only(),
+                );
                 *subscreen = SubScreen::SettingsPage(settings_page);
                 Action::Run(task.map(Message::SettingsPage))
             }
 
+            Message::ExportPage(message) => {
+                let State::Ready { subscreen } = &mut self.state else {
+                    return Action::None;
+                };
+
+                let SubScreen::ExportPage(export_page) = subscreen else {
+                    return Action::None;
+                };
+
+                match export_page.update(message, now) {
+                    export::Action::None => Action::None,
+                    export::Action::Back => self.update(Message::OpenSettingsPage, now),
+                    export::Action::Run(task) => Action::Run(task.map(Message::ExportPage)),
+                    export::Action::AddToast(toast) => Action::AddToast(toast),
+                    export::Action::VerifyPassword(password) => {
+                        let db_clone = Arc::clone(&self.database);
+                        Action::Run(Task::perform(
+                            async move { db_clone.verify_password(password).await },
+                            |res| Message::ExportPage(export::Message::PasswordVerified(res)),
+                        ))
+                    }
+                    export::Action::ExportContent(path_buf, entry_ids) => {
+                        let db_clone = Arc::clone(&self.database);
+                        Action::Run(Task::perform(
+                            async move {
+                                db_clone
+                                    .export_content(path_buf.clone(), entry_ids)
+                                    .await
+                                    .map(|_| path_buf)
+                            },
+                            Message::PlaintextExported,
+                        ))
+                    }
+                    export::Action::ExportPaperBackup(path_buf, entry_ids) => {
+                        let db_clone = Arc::clone(&self.database);
+                        Action::Run(Task::perform(
+                            async move {
+                                db_clone
+                                    .export_paper_backup(path_buf.clone(), entry_ids)
+                                    .await
+                                    .map(|_| path_buf)
+                            },
+                            Message::PlaintextExported,
+                        ))
+                    }
+                    export::Action::ExportKeePassVault {
+                        path,
+                        entry_ids,
+                        password,
+                        key_file,
+                    } => {
+                        let db_clone = Arc::clone(&self.database);
+                        Action::Run(Task::perform(
+                            async move {
+                                db_clone
+                                    .export_keepass_vault(
+                                        path.clone(),
+                                        entry_ids,
+                                        password,
+                                        key_file,
+                                    )
+                                    .await
+                                    .map(|_| path)
+                            },
+                            Message::VaultExported,
+                        ))
+                    }
+                    export::Action::ShowMigrationCodes(entry_ids) => {
+                        let db_clone = Arc::clone(&self.database);
+                        Action::Run(Task::perform(
+                            async move { db_clone.export_migration_uris(entry_ids).await },
+                            Message::MigrationCodesLoaded,
+                        ))
+                    }
+                }
+            }
+            Message::ExportEntriesLoaded(format, result) => {
+                let State::Ready { subscreen, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                match result {
+                    Ok(entries) => {
+                        let (export_page, task) = export::ExportPage::new(format, entries);
+                        *subscreen = SubScreen::ExportPage(export_page);
+                        Action::Run(task.map(Message::ExportPage))
+                    }
+                    Err(err) => {
+                        error!("{err}");
+                        Action::AddToast(Toast::error_toast(err))
+                    }
+                }
+            }
+
+            Message::BackupsPage(message) => {
+                let State::Ready { subscreen } = &mut self.state else {
+                    return Action::None;
+                };
+
+                let SubScreen::BackupsPage(backups_page) = subscreen else {
+                    return Action::None;
+                };
+
+                match backups_page.update(message, now) {
+                    backups::Action::None => Action::None,
+                    backups::Action::Back => self.update(Message::OpenSettingsPage, now),
+                    backups::Action::AddToast(toast) => Action::AddToast(toast),
+                    backups::Action::Restore(path, password) => {
+                        let db_clone = Arc::clone(&self.database);
+                        Action::Run(Task::perform(
+                            async move { db_clone.restore_backup(path, password).await },
+                            |res| Message::BackupsPage(backups::Message::Restored(res)),
+                        ))
+                    }
+                    // the session password doesn't open the restored vault anymore
+                    backups::Action::Restored {
+                        same_password: false,
+                    } => Action::RunAndToast(
+                        Task::done(Message::Lock),
+                        Toast::success_toast(
+                            "Backup restored, unlock the vault with the password of the backup",
+                        ),
+                    ),
+                    backups::Action::Restored {
+                        same_password: true,
+                    } => Action::RunAndToast(
+                        Task::done(Message::LoadEntries),
+                        Toast::success_toast("Backup restored"),
+                    ),
+                }
+            }
+            Message::BackupsLoaded(result) => {
+                let State::Ready { subscreen, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                match result.map(|backups| (backups, self.database.backups_dir().clone())) {
+                    Ok((backups, folder)) => {
+                        let (backups_page, task) = backups::BackupsPage::new(
+                            backups,
+                            folder,
+                            self.database.is_read_only(),
+                        );
+                        *subscreen = SubScreen::BackupsPage(backups_page);
+                        Action::Run(task.map(Message::BackupsPage))
+                    }
+                    Err(err) => {
+                        error!("{err}");
+                        Action::AddToast(Toast::error_toast(err))
+                    }
+                }
+            }
+
+            Message::MigrationPage(message) => {
+                let State::Ready { subscreen } = &mut self.state else {
+                    return Action::None;
+                };
+
+                let SubScreen::MigrationPage(migration_page) = subscreen else {
+                    return Action::None;
+                };
+
+                match migration_page.update(message, now) {
+                    migration::Action::None => Action::None,
+                    migration::Action::Back => self.update(Message::LoadEntries, now),
+                    migration::Action::Run(task) => Action::Run(task.map(Message::MigrationPage)),
+                    migration::Action::AddToast(toast) => Action::AddToast(toast),
+                }
+            }
+            Message::MigrationCodesLoaded(result) => {
+                let State::Ready { subscreen, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                match result.and_then(migration::MigrationPage::new) {
+                    Ok((migration_page, task)) => {
+                        *subscreen = SubScreen::MigrationPage(migration_page);
+                        Action::Run(task.map(Message::MigrationPage))
+                    }
+                    Err(err) => {
+                        error!("{err}");
+                        Action::AddToast(Toast::error_toast(err))
+                    }
+                }
+            }
+
             Message::RefreshCodes => {
                 // This forces a re-render every second
                 // Since view() calls totp.generate_current(), codes will update automatically
@@ -280,27 +811,473 @@ impl HomePage {
                     return Action::None;
                 };
 
+                // Pages with pending edits keep them, saving merges them into the changed file
                 let SubScreen::Home { entries: _ } = subscreen else {
                     return Action::None;
                 };
 
                 self.update(Message::LoadEntries, now)
             }
+            Message::SearchChanged(search) => {
+                self.search = search;
+                Action::None
+            }
+
+            Message::SharedVaultPasswordChanged(path, value) => {
+                if let Some(MountState::Locked { password, .. }) = self
+                    .shared_vault_mut(&path)
+                    .map(|mounted| &mut mounted.state)
+                {
+                    *password = value;
+                }
+                Action::None
+            }
+            Message::UnlockSharedVault(path) => {
+                let Some(mounted) = self.shared_vault_mut(&path) else {
+                    return Action::None;
+                };
+
+                let read_only = !mounted.vault.writable;
+                let MountState::Locked {
+                    password,
+                    unlocking,
+                } = &mut mounted.state
+                else {
+                    return Action::None;
+                };
+
+                if password.is_empty() || *unlocking {
+                    return Action::None;
+                }
+                *unlocking = true;
+                let password = std::mem::take(password);
+
+                let vault_path = path.clone();
+                Action::Run(Task::perform(
+                    async move {
+                        let database =
+                            unlock_database(vault_path.clone(), password.into(), read_only).await?;
+                        // kept out of the shared folder, other members don't need our copies
+                        Ok::<_, anywho::Error>(
+                            database.with_backups_dir(shared_backups_dir(&vault_path)?),
+                        )
+                    },
+                    move |result| Message::SharedVaultUnlocked(path, Box::from(result)),
+                ))
+            }
+            Message::SharedVaultUnlocked(path, result) => {
+                let Some(mounted) = self.shared_vault_mut(&path) else {
+                    return Action::None;
+                };
+
+                match *result {
+                    Ok(database) => {
+                        let database = Arc::new(database);
+                        let task = load_shared_entries(&database);
+                        mounted.state = MountState::Unlocked {
+                            database,
+                            entries: Vec::new(),
+                        };
+                        self.apply_backup_policy();
+                        Action::Run(task)
+                    }
+                    Err(err) => {
+                        error!("{err}");
+                        let name = mounted.vault.name();
+                        *mounted = MountedVault::new(mounted.vault.clone());
+                        Action::AddToast(Toast::error_toast(format!("{name}: {err}")))
+                    }
+                }
+            }
+            Message::SharedEntriesLoaded(path, result) => {
+                let Some(mounted) = self.shared_vault_mut(&path) else {
+                    return Action::None;
+                };
+
+                match result {
+                    Ok(loaded) => {
+                        if let MountState::Unlocked { entries, .. } = &mut mounted.state {
+                            *entries = loaded;
+                        }
+                        Action::None
+                    }
+                    Err(err) => {
+                        error!("{err}");
+                        Action::AddToast(Toast::error_toast(err))
+                    }
+                }
+            }
+            Message::SharedVaultChangedOnDisk(path) => {
+                let Some(database) = self
+                    .shared_vaults
+                    .iter()
+                    .find(|mounted| mounted.vault.path == path)
+                    .and_then(MountedVault::database)
+                else {
+                    return Action::None;
+                };
+
+                if !database.has_changed_on_disk() {
+                    info!("Ignoring filesystem event: no external change");
+                    return Action::None;
+                }
+
+                Action::Run(load_shared_entries(database))
+            }
+            Message::LockSharedVault(path) => {
+                if let Some(mounted) = self.shared_vault_mut(&path) {
+                    *mounted = MountedVault::new(mounted.vault.clone());
+                }
+                Action::None
+            }
+
+            Message::FileDropped(path) => {
+                let State::Ready { subscreen } = &mut self.state else {
+                    return Action::None;
+                };
+
+                let SubScreen::Home { entries: _ } = subscreen else {
+                    return Action::None;
+                };
+
+                if self.database.is_read_only() {
+                    return Action::AddToast(Toast::error_toast(
+                        "The vault is open read-only, nothing can be added",
+                    ));
+                }
+
+                if is_qr_image(&path) {
+                    let (upsert_page, task) = upsert::UpsertPage::new(None);
+                    *subscreen = SubScreen::UpsertPage(upsert_page);
+                    self.editing = None;
+                    Action::Run(Task::batch([
+                        task.map(Message::UpsertPage),
+                        Task::done(Message::UpsertPage(upsert::Message::QrFileDropped(path))),
+                    ]))
+                } else {
+                    let db_clone = Arc::clone(&self.database);
+                    Action::Run(Task::perform(
+                        async move { db_clone.import_content(path).await },
+                        Message::ContentImported,
+                    ))
+                }
+            }
+
+            Message::OpenUris(uris) => {
+                self.pending_uris.extend(uris);
+
+                if self.database.is_read_only() {
+                    self.pending_uris.clear();
+                    return Action::AddToast(Toast::error_toast(
+                        "The vault is open read-only, the received entry can't be added",
+                    ));
+                }
+
+                // Only replace the home screen, anything else may hold unsaved changes
+                let State::Ready { subscreen } = &mut self.state else {
+                    return Action::None;
+                };
+
+                let SubScreen::Home { entries: _ } = subscreen else {
+                    return Action::AddToast(Toast::success_toast(
+                        "The received entry will open once you are back on the home screen",
+                    ));
+                };
+
+                let uris = std::mem::take(&mut self.pending_uris);
+                let (upsert_page, task) = upsert::UpsertPage::new(None);
+                *subscreen = SubScreen::UpsertPage(upsert_page);
+                self.editing = None;
+                Action::Run(Task::batch([
+                    task.map(Message::UpsertPage),
+                    Task::done(Message::UpsertPage(upsert::Message::UrisReceived(uris))),
+                ]))
+            }
+
+            #[cfg(unix)]
+            Message::IpcRequest(request) => {
+                if self.is_trusted(&request.client) {
+                    Action::Run(self.answer_ipc_request(request))
+                } else {
+                    self.ask_for_access(AccessRequest::Ipc(request));
+                    Action::None
+                }
+            }
+            #[cfg(unix)]
+            Message::AccessDecision(decision) => {
+                let Some(request) = self.access_prompts.pop_front() else {
+                    return Action::None;
+                };
+
+                match decision {
+                    AccessDecision::Deny => {
+                        request.reject("The request was denied");
+                        Action::None
+                    }
+                    AccessDecision::AllowOnce => Action::Run(self.allow_access(request)),
+                    AccessDecision::AllowAlways => {
+                        let save = self.trust_client(request.client());
+                        Action::Run(Task::batch([self.allow_access(request), save]))
+                    }
+                }
+            }
+            #[cfg(unix)]
+            Message::IpcEntriesLoaded(request, result) => match result {
+                Ok(entries) => {
+                    request.answer(&entries);
+                    Action::AddToast(Toast::success_toast(format!(
+                        "Allowed {} to {}",
+                        request.client, request.request
+                    )))
+                }
+                Err(err) => {
+                    error!("{err}");
+                    request.reject(err.to_string());
+                    Action::AddToast(Toast::error_toast(err))
+                }
+            },
+            #[cfg(unix)]
+            Message::IpcConfigSaved(result) => match result {
+                Ok(_) => Action::None,
+                Err(err) => Action::AddToast(Toast::error_toast(err)),
+            },
+
+            #[cfg(unix)]
+            Message::DbusRequest(request) => {
+                let db_clone = Arc::clone(&self.database);
+                Action::Run(Task::perform(
+                    async move { db_clone.list_entries().await },
+                    move |result| Message::DbusEntriesLoaded(request, result),
+                ))
+            }
+            #[cfg(unix)]
+            Message::DbusEntriesLoaded(request, result) => {
+                let entries = match result {
+                    Ok(entries) => entries,
+                    Err(err) => {
+                        error!("{err}");
+                        request.reject(&err.to_string());
+                        return Action::None;
+                    }
+                };
+
+                match request {
+                    dbus::Request::ListEntries(responder) => {
+                        responder.respond(Ok(entries
+                            .iter()
+                            .filter_map(|entry| {
+                                Some((
+                                    entry.id?.to_string(),
+                                    entry.name.clone(),
+                                    entry.totp.issuer.clone().unwrap_or_default(),
+                                ))
+                            })
+                            .collect()));
+                        Action::None
+                    }
+                    dbus::Request::CopyCode(id, client, responder) => {
+                        let Some(entry) = entries.into_iter().find(|entry| {
+                            entry.id.is_some_and(|entry_id| entry_id.to_string() == id)
+                        }) else {
+                            responder.respond(Err(format!("No entry with id {id}")));
+                            return Action::None;
+                        };
+
+                        // any program of the session can call it, so it's allowed like IPC requests
+                        if self.is_trusted(&client) {
+                            Action::Run(copy_dbus_code(&entry, responder))
+                        } else {
+                            self.ask_for_access(AccessRequest::DbusCopyCode(
+                                entry, client, responder,
+                            ));
+                            Action::None
+                        }
+                    }
+                    other => {
+                        other.reject("Unexpected request");
+                        Action::None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Mounts the shared vaults of the config that aren't yet and drops the removed ones. Vaults
+    /// whose write permission changed are locked again, it's decided when unlocking them.
+    fn sync_shared_vaults(&mut self) {
+        let configured = self
+            .config
+            .lock()
+            .map(|cfg| cfg.shared_vaults.clone())
+            .unwrap_or_default();
+
+        let mut mounted = std::mem::take(&mut self.shared_vaults);
+        self.shared_vaults = configured
+            .into_iter()
+            // the personal vault is already shown
+            .filter(|vault| vault.path != *self.database.path())
+            .map(
+                |vault| match mounted.iter().position(|m| m.vault == vault) {
+                    Some(index) => mounted.swap_remove(index),
+                    None => MountedVault::new(vault),
+                },
+            )
+            .collect();
+    }
+
+    /// Applies the backup settings of the config to the personal vault and the unlocked shared
+    /// ones, they are changed on the [`SettingsPage`]
+    fn apply_backup_policy(&self) {
+        let Ok(policy) = self.config.lock().map(|cfg| cfg.backup_policy()) else {
+            return;
+        };
+
+        self.database.set_backup_policy(policy);
+        for database in self.shared_vaults.iter().filter_map(MountedVault::database) {
+            database.set_backup_policy(policy);
+        }
+    }
+
+    fn shared_vault_mut(&mut self, path: &Path) -> Option<&mut MountedVault> {
+        self.shared_vaults
+            .iter_mut()
+            .find(|mounted| mounted.vault.path == path)
+    }
+
+    /// Runs a write on the vault the open [`UpsertPage`] or [`ConflictPage`] edits, refusing it
+    /// if that is a shared vault that got locked or removed meanwhile
+    fn write_editing<T, F>(
+        &self,
+        write: impl FnOnce(Arc<ClockodeDatabase>) -> F,
+        on_done: impl FnOnce(T) -> Message + Send + 'static,
+    ) -> Action
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let database = match &self.editing {
+            None => Some(Arc::clone(&self.database)),
+            Some(path) => self
+                .shared_vaults
+                .iter()
+                .find(|mounted| &mounted.vault.path == path)
+                .and_then(MountedVault::database)
+                .cloned(),
+        };
+
+        match database {
+            Some(database) => Action::Run(Task::perform(write(database), on_done)),
+            None => Action::AddToast(Toast::error_toast(
+                "The shared vault was locked, the change was not saved",
+            )),
         }
     }
 
+    /// Loads the entries to answer an allowed IPC request with
+    #[cfg(unix)]
+    fn answer_ipc_request(&self, request: ipc::IpcRequest) -> Task<Message> {
+        let db_clone = Arc::clone(&self.database);
+        Task::perform(
+            async move { db_clone.list_entries().await },
+            move |result| Message::IpcEntriesLoaded(request, result),
+        )
+    }
+
+    /// Whether the requests of the given client are answered without asking
+    #[cfg(unix)]
+    fn is_trusted(&self, client: &ipc::Client) -> bool {
+        client.trusted_program().is_some_and(|program| {
+            self.config
+                .lock()
+                .is_ok_and(|cfg| cfg.ipc_trusted_programs.contains(&program))
+        })
+    }
+
+    /// Queues the request to be allowed or denied by the user. It's refused right away if the
+    /// same process is already waiting for an answer or too many requests are waiting.
+    #[cfg(unix)]
+    fn ask_for_access(&mut self, request: AccessRequest) {
+        let pid = request.client().pid;
+        if self
+            .access_prompts
+            .iter()
+            .any(|pending| pending.client().pid == pid)
+        {
+            request.reject("Another request of this program is waiting for an answer");
+        } else if self.access_prompts.len() >= MAX_ACCESS_PROMPTS {
+            request.reject("Too many requests are waiting for an answer");
+        } else {
+            self.access_prompts.push_back(request);
+        }
+    }
+
+    /// Answers a request the user allowed
+    #[cfg(unix)]
+    fn allow_access(&self, request: AccessRequest) -> Task<Message> {
+        match request {
+            AccessRequest::Ipc(request) => self.answer_ipc_request(request),
+            AccessRequest::DbusCopyCode(entry, _, responder) => copy_dbus_code(&entry, responder),
+        }
+    }
+
+    /// Remembers the program of the given client so its requests are answered without asking
+    #[cfg(unix)]
+    fn trust_client(&self, client: &ipc::Client) -> Task<Message> {
+        let Some(program) = client.trusted_program() else {
+            return Task::none();
+        };
+
+        let Ok(mut cfg) = self.config.lock() else {
+            error!("Warning: config mutex poisoned. Cannot trust the IPC client.");
+            return Task::none();
+        };
+
+        if !cfg.ipc_trusted_programs.contains(&program) {
+            cfg.ipc_trusted_programs.push(program);
+        }
+        let cfg_clone = cfg.clone();
+
+        Task::perform(
+            async move { cfg_clone.save(APP_ID).await },
+            Message::IpcConfigSaved,
+        )
+    }
+
     pub fn subscription(&self, now: Instant) -> Subscription<Message> {
         let watcher = watch_database((*self.database.path()).clone())
             .map(|_| Message::DatabaseChangedOnDisk);
 
+        // Unlocked shared vaults are reloaded when changed on disk too
+        let shared_watchers = Subscription::batch(
+            self.shared_vaults
+                .iter()
+                .filter(|mounted| mounted.database().is_some())
+                .map(|mounted| {
+                    watch_database(mounted.vault.path.clone())
+                        .with(mounted.vault.path.clone())
+                        .map(|(path, _)| Message::SharedVaultChangedOnDisk(path))
+                }),
+        );
+
         let screen_subscription = match &self.state {
             State::Loading => Subscription::none(),
             State::Ready { subscreen } => match subscreen {
                 SubScreen::Home { entries } => {
-                    if entries.is_empty() {
-                        Subscription::none()
+                    let file_drops = event::listen_with(handle_event);
+                    let shared_entries = self.shared_vaults.iter().any(|mounted| {
+                        matches!(
+                            &mounted.state,
+                            MountState::Unlocked { entries, .. } if !entries.is_empty()
+                        )
+                    });
+                    if entries.is_empty() && !shared_entries {
+                        file_drops
                     } else {
-                        iced::time::every(Duration::from_secs(1)).map(|_| Message::RefreshCodes)
+                        Subscription::batch([
+                            iced::time::every(Duration::from_secs(1))
+                                .map(|_| Message::RefreshCodes),
+                            file_drops,
+                        ])
                     }
                 }
                 SubScreen::UpsertPage(upsert_page) => {
@@ -309,15 +1286,37 @@ impl HomePage {
                 SubScreen::SettingsPage(settings_page) => {
                     settings_page.subscription(now).map(Message::SettingsPage)
                 }
+                SubScreen::MigrationPage(migration_page) => {
+                    migration_page.subscription(now).map(Message::MigrationPage)
+                }
+                SubScreen::ExportPage(export_page) => {
+                    export_page.subscription(now).map(Message::ExportPage)
+                }
+                SubScreen::ConflictPage(conflict_page) => {
+                    conflict_page.subscription(now).map(Message::ConflictPage)
+                }
+                SubScreen::BackupsPage(backups_page) => {
+                    backups_page.subscription(now).map(Message::BackupsPage)
+                }
             },
         };
 
-        Subscription::batch([screen_subscription, watcher])
+        #[cfg(unix)]
+        if self.config.lock().is_ok_and(|cfg| cfg.ipc_enabled) {
+            return Subscription::batch([
+                screen_subscription,
+                watcher,
+                shared_watchers,
+                ipc::listen().map(Message::IpcRequest),
+            ]);
+        }
+
+        Subscription::batch([screen_subscription, watcher, shared_watchers])
     }
 }
 
-/// View of the header of this screen
-fn header_view<'a>(entry_count: usize) -> Element<'a, Message> {
+/// View of the header of this screen, without the add button when the vault is read-only
+fn header_view<'a>(entry_count: usize, read_only: bool) -> Element<'a, Message> {
     row![
         // Title section
         column![
@@ -326,9 +1325,10 @@ fn header_view<'a>(entry_count: usize) -> Element<'a, Message> {
                 .size(style::font_size::SMALL)
                 .style(style::muted_text),
             text(format!(
-                "{} {}",
+                "{} {}{}",
                 entry_count,
-                if entry_count == 1 { "Entry" } else { "Entries" }
+                if entry_count == 1 { "Entry" } else { "Entries" },
+                if read_only { " · Read-only" } else { "" }
             ))
             .size(style::font_size::SMALL)
             .style(style::muted_text)
@@ -336,17 +1336,27 @@ fn header_view<'a>(entry_count: usize) -> Element<'a, Message> {
         .spacing(style::spacing::TINY),
         space().width(Length::Fill),
         // Action buttons
-        row![
-            button(icons::get_icon("list-add-symbolic", 21))
-                .on_press(Message::OpenUpsertPage(None))
-                .padding(8)
-                .style(style::primary_button),
-            button(icons::get_icon("emblem-system-symbolic", 21))
-                .on_press(Message::OpenSettingsPage)
-                .padding(8)
-                .style(style::secondary_button),
-        ]
-        .spacing(style::spacing::SMALL)
+        row![]
+            .extend((!read_only).then(|| {
+                button(icons::get_icon("list-add-symbolic", 21))
+                    .on_press(Message::OpenUpsertPage(None))
+                    .padding(8)
+                    .style(style::primary_button)
+                    .into()
+            }))
+            .push(
+                button(icons::get_icon("emblem-system-symbolic", 21))
+                    .on_press(Message::OpenSettingsPage)
+                    .padding(8)
+                    .style(style::secondary_button),
+            )
+            .push(
+                button(icons::get_icon("system-lock-screen-symbolic", 21))
+                    .on_press(Message::Lock)
+                    .padding(8)
+                    .style(style::secondary_button),
+            )
+            .spacing(style::spacing::SMALL)
     ]
     .spacing(style::spacing::LARGE)
     .padding(10)
@@ -355,13 +1365,24 @@ fn header_view<'a>(entry_count: usize) -> Element<'a, Message> {
     .into()
 }
 
-/// View of the contents of this screen
-fn content_view<'a>(entries: &'a [ClockodeEntry]) -> Element<'a, Message> {
-    if entries.is_empty() {
+/// View of the contents of this screen: the personal entries, followed by a section for every
+/// shared vault, all filtered by `search`. Entries of read-only vaults have no edit button.
+fn content_view<'a>(
+    entries: &'a [ClockodeEntry],
+    read_only: bool,
+    shared_vaults: &'a [MountedVault],
+    search: &str,
+) -> Element<'a, Message> {
+    if entries.is_empty() && shared_vaults.is_empty() {
         container(
             column![
                 text("No TOTP entries found").size(style::font_size::TITLE),
-                text("Add your first entry to get started").size(style::font_size::BODY),
+                text(if read_only {
+                    "This vault is open read-only"
+                } else {
+                    "Add your first entry to get started"
+                })
+                .size(style::font_size::BODY),
             ]
             .align_x(Alignment::Center)
             .spacing(style::spacing::MEDIUM),
@@ -369,61 +1390,306 @@ fn content_view<'a>(entries: &'a [ClockodeEntry]) -> Element<'a, Message> {
         .center(Length::Fill)
         .into()
     } else {
-        let entries_list = entries.iter().fold(
-            Column::new()
-                .height(Length::Fill)
-                .spacing(style::spacing::MEDIUM)
-                .padding(10),
-            |col, entry| {
-                let code = entry.totp.generate_current().unwrap_or_default();
-                let time_remaining = get_time_until_next_totp_refresh(entry.totp.step);
-
-                let entry_view = container(
-                    row![
-                        column![
-                            text(&entry.name)
-                                .wrapping(text::Wrapping::Glyph)
-                                .size(style::font_size::LARGE),
-                            row![
-                                text(format!(
-                                    "{} digits · {}s",
-                                    entry.totp.digits, time_remaining
-                                ))
-                                .size(style::font_size::SMALL)
-                                .style(style::muted_text),
-                                dot(time_remaining)
-                            ]
-                            .align_y(Alignment::Start)
-                            .spacing(style::spacing::SMALL),
-                        ]
-                        .spacing(style::spacing::TINY)
-                        .width(Length::Fill),
-                        column![
-                            text(code.clone())
-                                .size(style::font_size::HERO)
-                                .font(iced::Font::MONOSPACE)
-                        ]
-                        .spacing(style::spacing::TINY)
-                        .align_x(iced::Alignment::End),
-                        button(icons::get_icon("edit-copy-symbolic", 21))
-                            .on_press(Message::CopyToClipboard(code))
-                            .padding(8)
-                            .style(style::primary_button),
-                        button(icons::get_icon("edit-symbolic", 21))
-                            .on_press(Message::OpenUpsertPage(Some(entry.clone())))
-                            .padding(8)
-                            .style(style::secondary_button),
-                    ]
-                    .spacing(style::spacing::SMALL)
-                    .padding(16)
-                    .align_y(iced::Alignment::Center),
-                )
-                .style(style::entry_card);
-
-                col.push(entry_view)
-            },
-        );
+        let personal = entry_cards(entries, search, |entry| {
+            (!read_only).then(|| Message::OpenUpsertPage(Some(entry.clone())))
+        });
+
+        let entries_list = Column::new()
+            .height(Length::Fill)
+            .spacing(style::spacing::MEDIUM)
+            .padding(10);
+
+        // Without shared vaults the list looks like it always did, no sections
+        let entries_list = if shared_vaults.is_empty() {
+            entries_list.extend(personal)
+        } else {
+            shared_vaults.iter().fold(
+                entries_list
+                    .push(section_title(String::from("My Vault"), read_only, None))
+                    .extend(personal),
+                |col, mounted| col.extend(shared_vault_view(mounted, search)),
+            )
+        };
 
         scrollable(entries_list).height(Length::Fill).into()
     }
 }
+
+/// Search field filtering the entries of every vault
+fn search_view<'a>(search: &'a str) -> Element<'a, Message> {
+    container(
+        text_input("Search", search)
+            .on_input(Message::SearchChanged)
+            .padding(10)
+            .size(style::font_size::BODY),
+    )
+    .padding([0, 10])
+    .into()
+}
+
+/// Whether the name, issuer or account of the entry contain the search text, ignoring case
+fn matches_search(entry: &ClockodeEntry, search: &str) -> bool {
+    let search = search.trim().to_lowercase();
+
+    search.is_empty()
+        || [
+            Some(&entry.name),
+            entry.totp.issuer.as_ref(),
+            Some(&entry.totp.account_name),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(&search))
+}
+
+/// Cards of the entries matching `search`, or a note if none does. `on_edit` gives the message
+/// of the edit button of an entry, none for read-only vaults.
+fn entry_cards<'a>(
+    entries: &'a [ClockodeEntry],
+    search: &str,
+    on_edit: impl Fn(&ClockodeEntry) -> Option<Message>,
+) -> Vec<Element<'a, Message>> {
+    let cards: Vec<Element<Message>> = entries
+        .iter()
+        .filter(|entry| matches_search(entry, search))
+        .map(|entry| entry_card(entry, on_edit(entry)))
+        .collect();
+
+    if cards.is_empty() {
+        let note = if entries.is_empty() {
+            "No entries"
+        } else {
+            "No entries match the search"
+        };
+        vec![
+            text(note)
+                .size(style::font_size::BODY)
+                .style(style::muted_text)
+                .into(),
+        ]
+    } else {
+        cards
+    }
+}
+
+/// Card of an entry with its current code
+fn entry_card<'a>(entry: &'a ClockodeEntry, on_edit: Option<Message>) -> Element<'a, Message> {
+    let code = entry.totp.generate_current().unwrap_or_default();
+    let time_remaining = get_time_until_next_totp_refresh(entry.totp.step);
+
+    container(
+        row![
+            column![
+                text(&entry.name)
+                    .wrapping(text::Wrapping::Glyph)
+                    .size(style::font_size::LARGE),
+                row![
+                    text(format!(
+                        "{} digits · {}s",
+                        entry.totp.digits, time_remaining
+                    ))
+                    .size(style::font_size::SMALL)
+                    .style(style::muted_text),
+                    dot(time_remaining)
+                ]
+                .align_y(Alignment::Start)
+                .spacing(style::spacing::SMALL),
+            ]
+            .spacing(style::spacing::TINY)
+            .width(Length::Fill),
+            column![
+                text(code.clone())
+                    .size(style::font_size::HERO)
+                    .font(iced::Font::MONOSPACE)
+            ]
+            .spacing(style::spacing::TINY)
+            .align_x(iced::Alignment::End),
+            button(icons::get_icon("edit-copy-symbolic", 21))
+                .on_press(Message::CopyToClipboard(code))
+                .padding(8)
+                .style(style::primary_button),
+        ]
+        .extend(on_edit.map(|message| {
+            button(icons::get_icon("edit-symbolic", 21))
+                .on_press(message)
+                .padding(8)
+                .style(style::secondary_button)
+                .into()
+        }))
+        .spacing(style::spacing::SMALL)
+        .padding(16)
+        .align_y(iced::Alignment::Center),
+    )
+    .style(style::entry_card)
+    .into()
+}
+
+/// Title of a vault section, with a lock button for shared vaults that are unlocked
+fn section_title<'a>(
+    name: String,
+    read_only: bool,
+    on_lock: Option<Message>,
+) -> Element<'a, Message> {
+    let title = if read_only {
+        format!("{name} · Read-only")
+    } else {
+        name
+    };
+
+    row![
+        text(title)
+            .size(style::font_size::MEDIUM)
+            .style(style::label_text),
+        space().width(Length::Fill),
+    ]
+    .extend(on_lock.map(|message| {
+        button(icons::get_icon("system-lock-screen-symbolic", 21))
+            .on_press(message)
+            .padding(6)
+            .style(style::secondary_button)
+            .into()
+    }))
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// Section of a shared vault: the password field while locked, its entries once unlocked
+fn shared_vault_view<'a>(mounted: &'a MountedVault, search: &str) -> Vec<Element<'a, Message>> {
+    let path = &mounted.vault.path;
+
+    match &mounted.state {
+        MountState::Locked {
+            password,
+            unlocking,
+        } => {
+            let submit = (!password.is_empty() && !unlocking)
+                .then(|| Message::UnlockSharedVault(path.clone()));
+
+            vec![
+                section_title(mounted.vault.name(), !mounted.vault.writable, None),
+                row![
+                    text_input("Password", password)
+                        .secure(true)
+                        .on_input(move |value| {
+                            Message::SharedVaultPasswordChanged(path.clone(), value)
+                        })
+                        .on_submit_maybe(submit.clone())
+                        .padding(8)
+                        .size(style::font_size::BODY),
+                    button(text("Unlock").size(style::font_size::BODY))
+                        .on_press_maybe(submit)
+                        .padding(8)
+                        .style(style::primary_button),
+                ]
+                .spacing(style::spacing::SMALL)
+                .align_y(Alignment::Center)
+                .into(),
+            ]
+        }
+        MountState::Unlocked { database, entries } => {
+            let read_only = database.is_read_only();
+            let title = section_title(
+                mounted.vault.name(),
+                read_only,
+                Some(Message::LockSharedVault(path.clone())),
+            );
+
+            std::iter::once(title)
+                .chain(entry_cards(entries, search, |entry| {
+                    (!read_only).then(|| Message::OpenSharedUpsertPage(path.clone(), entry.clone()))
+                }))
+                .collect()
+        }
+    }
+}
+
+/// Loads the entries of an unlocked shared vault
+fn load_shared_entries(database: &Arc<ClockodeDatabase>) -> Task<Message> {
+    let db_clone = Arc::clone(database);
+    let path = (*database.path()).clone();
+
+    Task::perform(
+        async move { db_clone.reload_entries().await },
+        move |result| Message::SharedEntriesLoaded(path, result),
+    )
+}
+
+/// Answers an allowed D-Bus `CopyCode` call, copying the current code of `entry`
+#[cfg(unix)]
+fn copy_dbus_code(entry: &ClockodeEntry, responder: dbus::Responder<()>) -> Task<Message> {
+    match entry.totp.generate_current() {
+        Ok(code) => {
+            responder.respond(Ok(()));
+            Task::done(Message::CopyToClipboard(code))
+        }
+        Err(e) => {
+            responder.respond(Err(format!("Could not generate the code: {e}")));
+            Task::none()
+        }
+    }
+}
+
+/// Dialog asking the user whether to answer the given IPC or D-Bus request
+#[cfg(unix)]
+fn access_prompt_view<'a>(request: &'a AccessRequest, pending: usize) -> Element<'a, Message> {
+    let dialog = container(
+        column![
+            text("Allow Access?").size(style::font_size::TITLE),
+            text(format!(
+                "{} wants to {}.",
+                request.client(),
+                request.description()
+            ))
+            .size(style::font_size::BODY),
+            text(if pending > 1 {
+                format!("{} more requests are waiting.", pending - 1)
+            } else {
+                String::from("Only allow programs you started yourself.")
+            })
+            .size(style::font_size::BODY)
+            .style(style::muted_text),
+            row![
+                button(text("Deny").size(style::font_size::BODY))
+                    .on_press(Message::AccessDecision(AccessDecision::Deny))
+                    .padding(8)
+                    .style(style::secondary_button),
+                space().width(Length::Fill),
+                button(text("Allow Always").size(style::font_size::BODY))
+                    .on_press_maybe(
+                        request
+                            .client()
+                            .trusted_program()
+                            .is_some()
+                            .then_some(Message::AccessDecision(AccessDecision::AllowAlways))
+                    )
+                    .padding(8)
+                    .style(style::secondary_button),
+                button(text("Allow Once").size(style::font_size::BODY))
+                    .on_press(Message::AccessDecision(AccessDecision::AllowOnce))
+                    .padding(8)
+                    .style(style::primary_button),
+            ]
+            .spacing(style::spacing::SMALL)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(style::spacing::MEDIUM)
+        .padding(24)
+        .width(Length::Fill.max(500)),
+    )
+    .style(style::card_container);
+
+    container(dialog).padding(20).center(Length::Fill).into()
+}
+
+//
+// SUBSCRIPTIONS
+//
+
+fn handle_event(event: event::Event, _: event::Status, _: iced::window::Id) -> Option<Message> {
+    match event {
+        event::Event::Window(iced::window::Event::FileDropped(path)) => {
+            Some(Message::FileDropped(path))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use iced::{
+    Alignment, Element,
+    Length::{self},
+    Subscription, Task, event,
+    keyboard::{self, Key, key::Named},
+    time::Instant,
+    widget::{Column, button, column, container, row, scrollable, space, stack, text, text_input},
+};
+use secrecy::SecretString;
+
+use clockode_core::Backup;
+
+use crate::{
+    app::{utils::style, widgets::Toast},
+    icons,
+};
+
+pub struct BackupsPage {
+    /// Backups of the vault, newest first
+    backups: Vec<Backup>,
+    /// Folder the backups are kept in
+    folder: PathBuf,
+    /// The vault is open read-only, nothing can be restored
+    read_only: bool,
+    /// Shown after picking a backup, asks for the password of that backup
+    confirmation: Option<Confirmation>,
+}
+
+/// State of the restore confirmation
+struct Confirmation {
+    backup: Backup,
+    password: String,
+    restoring: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Go back a screen
+    Back,
+    /// Callback after pressing a [`Hotkey`] of this page
+    Hotkey(Hotkey),
+    /// Ask to restore the backup at the given index, opens the confirmation
+    SelectBackup(usize),
+    /// Update the backup password typed on the confirmation
+    UpdatePassword(String),
+    /// Close the confirmation without restoring
+    CancelConfirmation,
+    /// Accept the confirmation and restore the backup
+    Confirm,
+    /// Callback after restoring the backup, with whether the vault still opens with the
+    /// current password
+    Restored(Result<bool, anywho::Error>),
+}
+
+pub enum Action {
+    /// Does nothing
+    None,
+    /// Go back a screen
+    Back,
+    /// Add a new [`Toast`] to show
+    AddToast(Toast),
+    /// Ask parent to restore the backup at the given path, opened with the given password,
+    /// answering with [`Message::Restored`]
+    Restore(PathBuf, SecretString),
+    /// The backup was restored, the vault has to be unlocked again unless `same_password`
+    Restored { same_password: bool },
+}
+
+impl BackupsPage {
+    pub fn new(backups: Vec<Backup>, folder: PathBuf, read_only: bool) -> (Self, Task<Message>) {
+        (
+            Self {
+                backups,
+                folder,
+                read_only,
+                confirmation: None,
+            },
+            Task::none(),
+        )
+    }
+
+    pub fn view(&self, _now: Instant) -> iced::Element<'_, Message> {
+        let header = header_view(self.backups.len());
+        let content = backups_view(&self.backups, &self.folder, self.read_only);
+
+        let page = container(
+            container(column![header, content])
+                .padding(5.)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .center(Length::Fill);
+
+        match &self.confirmation {
+            Some(confirmation) => stack![page, confirmation_view(confirmation)].into(),
+            None => page.into(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message, _now: Instant) -> Action {
+        match message {
+            Message::Back => Action::Back,
+            Message::Hotkey(hotkey) => match hotkey {
+                Hotkey::Esc => {
+                    if self.confirmation.take().is_some() {
+                        Action::None
+                    } else {
+                        Action::Back
+                    }
+                }
+            },
+            Message::SelectBackup(index) => {
+                if self.read_only {
+                    return Action::AddToast(Toast::error_toast("The vault is open read-only"));
+                }
+
+                if let Some(backup) = self.backups.get(index) {
+                    self.confirmation = Some(Confirmation {
+                        backup: backup.clone(),
+                        password: String::new(),
+                        restoring: false,
+                    });
+                }
+                Action::None
+            }
+            Message::UpdatePassword(value) => {
+                if let Some(confirmation) = &mut self.confirmation {
+                    confirmation.password = value;
+                }
+                Action::None
+            }
+            Message::CancelConfirmation => {
+                self.confirmation = None;
+                Action::None
+            }
+            Message::Confirm => {
+                let Some(confirmation) = &mut self.confirmation else {
+                    return Action::None;
+                };
+
+                if confirmation.password.is_empty() || confirmation.restoring {
+                    return Action::None;
+                }
+
+                confirmation.restoring = true;
+                Action::Restore(
+                    confirmation.backup.path.clone(),
+                    confirmation.password.clone().into(),
+                )
+            }
+            Message::Restored(result) => match result {
+                Ok(same_password) => {
+                    self.confirmation = None;
+                    Action::Restored { same_password }
+                }
+                Err(err) => {
+                    if let Some(confirmation) = &mut self.confirmation {
+                        confirmation.password.clear();
+                        confirmation.restoring = false;
+                    }
+                    Action::AddToast(Toast::error_toast(err))
+                }
+            },
+        }
+    }
+
+    pub fn subscription(&self, _now: Instant) -> Subscription<Message> {
+        event::listen_with(handle_event)
+    }
+}
+
+/// View of the header of this screen
+fn header_view<'a>(count: usize) -> Element<'a, Message> {
+    row![
+        // Back button
+        button(
+            row![
+                icons::get_icon("go-previous-symbolic", 21),
+                text("Back").size(style::font_size::BODY)
+            ]
+            .spacing(style::spacing::TINY)
+            .align_y(iced::Alignment::Center)
+        )
+        .on_press(Message::Back)
+        .padding(8)
+        .style(style::secondary_button),
+        column![
+            text("Backups").size(style::font_size::TITLE),
+            text(format!(
+                "{} {}",
+                count,
+                if count == 1 { "Backup" } else { "Backups" }
+            ))
+            .size(style::font_size::SMALL)
+            .style(style::muted_text),
+        ]
+        .spacing(style::spacing::TINY),
+    ]
+    .spacing(style::spacing::LARGE)
+    .padding(10)
+    .align_y(iced::Alignment::Center)
+    .width(Length::Fill)
+    .into()
+}
+
+/// List of the backups, each with its restore button
+fn backups_view<'a>(
+    backups: &'a [Backup],
+    folder: &'a Path,
+    read_only: bool,
+) -> Element<'a, Message> {
+    let hint = if read_only {
+        String::from("The vault is open read-only, backups can't be restored.")
+    } else {
+        format!(
+            "Backups are kept in {}. Restoring one backs up the current vault first.",
+            folder.display()
+        )
+    };
+
+    let list = if backups.is_empty() {
+        Column::new().push(
+            text("No backups yet, one is taken before the next change to the vault")
+                .size(style::font_size::BODY)
+                .style(style::muted_text),
+        )
+    } else {
+        backups.iter().enumerate().fold(
+            Column::new().spacing(style::spacing::MEDIUM),
+            |col, (index, backup)| {
+                col.push(
+                    container(
+                        row![
+                            column![
+                                text(backup.created()).size(style::font_size::LARGE),
+                                text(format!("{:.1} KB", backup.size as f64 / 1024.0))
+                                    .size(style::font_size::SMALL)
+                                    .style(style::muted_text),
+                            ]
+                            .spacing(style::spacing::TINY)
+                            .width(Length::Fill),
+                            button(text("Restore").size(style::font_size::BODY))
+                                .on_press_maybe(
+                                    (!read_only).then_some(Message::SelectBackup(index))
+                                )
+                                .padding(8)
+                                .style(style::secondary_button),
+                        ]
+                        .spacing(style::spacing::SMALL)
+                        .padding(16)
+                        .align_y(Alignment::Center),
+                    )
+                    .style(style::entry_card),
+                )
+            },
+        )
+    };
+
+    scrollable(
+        container(
+            column![
+                text(hint)
+                    .size(style::font_size::SMALL)
+                    .style(style::muted_text),
+                list,
+            ]
+            .spacing(style::spacing::LARGE)
+            .padding(10)
+            .max_width(800),
+        )
+        .center_x(Length::Fill),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .into()
+}
+
+/// Modal asking for the password of the backup before replacing the vault with it
+fn confirmation_view<'a>(confirmation: &'a Confirmation) -> Element<'a, Message> {
+    let can_confirm = !confirmation.password.is_empty() && !confirmation.restoring;
+
+    let dialog = container(
+        column![
+            text("Restore Backup?").size(style::font_size::TITLE),
+            text(format!(
+                "The vault will be replaced with the backup from {}. Changes made since then \
+                 are only kept in the backup taken right before restoring.",
+                confirmation.backup.created()
+            ))
+            .size(style::font_size::BODY),
+            column![
+                text("Backup Password")
+                    .size(style::font_size::BODY)
+                    .style(style::label_text),
+                text_input(
+                    "The password of the vault when the backup was taken",
+                    &confirmation.password
+                )
+                .secure(true)
+                .on_input(Message::UpdatePassword)
+                .on_submit_maybe(can_confirm.then_some(Message::Confirm))
+                .padding(12)
+                .size(style::font_size::MEDIUM)
+            ]
+            .spacing(style::spacing::TINY),
+            row![
+                button(text("Cancel").size(style::font_size::BODY))
+                    .on_press(Message::CancelConfirmation)
+                    .padding(8)
+                    .style(style::secondary_button),
+                space().width(Length::Fill),
+                button(text("Restore").size(style::font_size::BODY))
+                    .on_press_maybe(can_confirm.then_some(Message::Confirm))
+                    .padding(8)
+                    .style(style::danger_button),
+            ]
+            .align_y(Alignment::Center),
+        ]
+        .spacing(style::spacing::MEDIUM)
+        .padding(24)
+        .width(Length::Fill.max(500)),
+    )
+    .style(style::card_container);
+
+    container(dialog).padding(20).center(Length::Fill).into()
+}
+
+//
+// SUBSCRIPTIONS
+//
+
+#[derive(Debug, Clone)]
+pub enum Hotkey {
+    Esc,
+}
+
+fn handle_event(event: event::Event, _: event::Status, _: iced::window::Id) -> Option<Message> {
+    #[allow(clippy::collapsible_match)]
+    match event {
+        event::Event::Keyboard(keyboard::Event::KeyPressed {
+            key, modifiers: _, ..
+        }) => match key {
+            Key::Named(Named::Escape) => Some(Message::Hotkey(Hotkey::Esc)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,248 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use iced::{
+    Alignment, Element,
+    Length::{self},
+    Subscription, Task, event,
+    keyboard::{self, Key, key::Named},
+    time::Instant,
+    widget::{button, column, container, row, space, text},
+};
+
+use clockode_core::{ClockodeEntry, EntryConflict, Resolution, format_utc_datetime};
+
+use crate::{app::utils::style, icons};
+
+pub struct ConflictPage {
+    conflict: EntryConflict,
+    /// Set once the user picked a resolution, until the parent is done saving it
+    resolving: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Go back a screen, keeping the version on disk
+    Back,
+    /// Callback after pressing a [`Hotkey`] of this page
+    Hotkey(Hotkey),
+    /// The user picked how to settle the conflict
+    Resolve(Resolution),
+}
+
+pub enum Action {
+    /// Go back a screen
+    Back,
+    /// Ask the parent to settle the conflict the given way
+    Resolve(EntryConflict, Resolution),
+}
+
+impl ConflictPage {
+    pub fn new(conflict: EntryConflict) -> (Self, Task<Message>) {
+        (
+            Self {
+                conflict,
+                resolving: false,
+            },
+            Task::none(),
+        )
+    }
+
+    pub fn view(&self, _now: Instant) -> iced::Element<'_, Message> {
+        let header = header_view(self.conflict.name());
+
+        let newer = self.conflict.ours_is_newer();
+        let ours = version_view(
+            "This Device",
+            self.conflict.ours.as_ref(),
+            newer == Some(true),
+            self.conflict.theirs.as_ref(),
+        );
+        let theirs = version_view(
+            "On Disk",
+            self.conflict.theirs.as_ref(),
+            newer == Some(false),
+            self.conflict.ours.as_ref(),
+        );
+
+        let both_exist = self.conflict.ours.is_some() && self.conflict.theirs.is_some();
+        let resolve = |resolution| (!self.resolving).then_some(Message::Resolve(resolution));
+
+        let actions = row![
+            button(text("Keep Both").size(style::font_size::BODY))
+                .on_press_maybe(resolve(Resolution::KeepBoth).filter(|_| both_exist))
+                .padding(8)
+                .style(style::secondary_button),
+            space().width(Length::Fill),
+            button(text("Keep Disk Version").size(style::font_size::BODY))
+                .on_press_maybe(resolve(Resolution::KeepTheirs))
+                .padding(8)
+                .style(style::secondary_button),
+            button(text("Keep This Version").size(style::font_size::BODY))
+                .on_press_maybe(resolve(Resolution::KeepOurs))
+                .padding(8)
+                .style(style::primary_button),
+        ]
+        .spacing(style::spacing::SMALL)
+        .align_y(Alignment::Center);
+
+        container(
+            column![
+                header,
+                column![
+                    text(
+                        "Another program (a sync tool or another Clockode window) changed this entry while you were editing it. Pick the version to keep."
+                    )
+                    .size(style::font_size::BODY)
+                    .style(style::muted_text),
+                    row![ours, theirs].spacing(style::spacing::MEDIUM),
+                    actions,
+                ]
+                .spacing(style::spacing::LARGE)
+                .padding(10)
+                .max_width(800),
+            ]
+            .width(Length::Fill)
+            .height(Length::Fill),
+        )
+        .padding(5.)
+        .center_x(Length::Fill)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message, _now: Instant) -> Action {
+        match message {
+            Message::Back => Action::Back,
+            Message::Hotkey(hotkey) => match hotkey {
+                Hotkey::Esc => Action::Back,
+            },
+            Message::Resolve(resolution) => {
+                self.resolving = true;
+                Action::Resolve(self.conflict.clone(), resolution)
+            }
+        }
+    }
+
+    pub fn subscription(&self, _now: Instant) -> Subscription<Message> {
+        event::listen_with(handle_event)
+    }
+}
+
+/// View of the header of this screen
+fn header_view<'a>(name: &str) -> Element<'a, Message> {
+    row![
+        // Back button
+        button(
+            row![
+                icons::get_icon("go-previous-symbolic", 21),
+                text("Back").size(style::font_size::BODY)
+            ]
+            .spacing(style::spacing::TINY)
+            .align_y(iced::Alignment::Center)
+        )
+        .on_press(Message::Back)
+        .padding(8)
+        .style(style::secondary_button),
+        column![
+            text("Resolve Conflict").size(style::font_size::TITLE),
+            text(format!("{name} was changed in two places"))
+                .size(style::font_size::SMALL)
+                .style(style::muted_text),
+        ]
+        .spacing(style::spacing::TINY),
+    ]
+    .spacing(style::spacing::LARGE)
+    .padding(10)
+    .align_y(iced::Alignment::Center)
+    .width(Length::Fill)
+    .into()
+}
+
+/// Card describing one side of the conflict, `other` is used to point out a changed secret
+fn version_view<'a>(
+    title: &'a str,
+    entry: Option<&'a ClockodeEntry>,
+    newer: bool,
+    other: Option<&'a ClockodeEntry>,
+) -> Element<'a, Message> {
+    let title = row![text(title).size(style::font_size::LARGE)]
+        .extend(newer.then(|| {
+            text("Newer")
+                .size(style::font_size::SMALL)
+                .style(style::label_text)
+                .into()
+        }))
+        .spacing(style::spacing::SMALL)
+        .align_y(Alignment::Center);
+
+    let details: Element<Message> = match entry {
+        Some(entry) => {
+            let secret_changed = other.is_some_and(|other| other.totp.secret != entry.totp.secret);
+
+            column![
+                text(&entry.name).size(style::font_size::MEDIUM),
+                text(format!(
+                    "{} · {}",
+                    entry.totp.issuer.as_deref().unwrap_or("No issuer"),
+                    entry.totp.account_name
+                ))
+                .size(style::font_size::BODY),
+                text(format!(
+                    "{} · {} digits · {}s",
+                    entry.totp.algorithm, entry.totp.digits, entry.totp.step
+                ))
+                .size(style::font_size::SMALL)
+                .style(style::muted_text),
+                text(match entry.modified {
+                    Some(modified) => format!("Saved {}", format_utc_datetime(modified)),
+                    None => String::from("Save time unknown"),
+                })
+                .size(style::font_size::SMALL)
+                .style(style::muted_text),
+            ]
+            .extend(secret_changed.then(|| {
+                text("Has a different secret")
+                    .size(style::font_size::SMALL)
+                    .style(style::label_text)
+                    .into()
+            }))
+            .spacing(style::spacing::TINY)
+            .into()
+        }
+        None => text("Deleted")
+            .size(style::font_size::MEDIUM)
+            .style(style::muted_text)
+            .into(),
+    };
+
+    container(
+        column![title, details]
+            .spacing(style::spacing::SMALL)
+            .padding(16)
+            .width(Length::Fill),
+    )
+    .style(style::entry_card)
+    .width(Length::FillPortion(1))
+    .into()
+}
+
+//
+// SUBSCRIPTIONS
+//
+
+#[derive(Debug, Clone)]
+pub enum Hotkey {
+    Esc,
+}
+
+fn handle_event(event: event::Event, _: event::Status, _: iced::window::Id) -> Option<Message> {
+    #[allow(clippy::collapsible_match)]
+    match event {
+        event::Event::Keyboard(keyboard::Event::KeyPressed {
+            key, modifiers: _, ..
+        }) => match key {
+            Key::Named(Named::Escape) => Some(Message::Hotkey(Hotkey::Esc)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,676 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{collections::HashSet, path::PathBuf};
+
+use iced::{
+    Alignment, Element,
+    Length::{self},
+    Subscription, Task, event,
+    keyboard::{self, Key, Modifiers, key::Named},
+    time::Instant,
+    widget::{
+        Column, button, checkbox, column, container,
+        operation::{focus_next, focus_previous},
+        row, scrollable, space, stack, text, text_input,
+    },
+};
+use rfd::{AsyncFileDialog, FileHandle};
+use secrecy::SecretString;
+use uuid::Uuid;
+
+use clockode_core::{ClockodeEntry, is_migration_compatible};
+
+use crate::{
+    app::{utils::style, widgets::Toast},
+    icons,
+};
+
+/// The different formats the vault content can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// Plain text file with one `otpauth://` URI per line
+    #[default]
+    UriList,
+    /// Google Authenticator `otpauth-migration://` QR codes
+    GoogleAuthenticator,
+    /// Printable HTML document with the QR code and secret of every entry
+    PaperBackup,
+    /// Brand new KDBX4 vault protected by its own password and/or key file
+    KeePassVault,
+}
+
+impl ExportFormat {
+    pub const ALL: &'static [Self] = &[
+        Self::UriList,
+        Self::GoogleAuthenticator,
+        Self::PaperBackup,
+        Self::KeePassVault,
+    ];
+
+    /// Returns true if this format exposes the secrets without any encryption
+    pub fn is_plaintext(&self) -> bool {
+        !matches!(self, ExportFormat::KeePassVault)
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::UriList => "Text file (otpauth URIs)",
+            ExportFormat::GoogleAuthenticator => "Google Authenticator QR codes",
+            ExportFormat::PaperBackup => "Printable paper backup (HTML)",
+            ExportFormat::KeePassVault => "KeePass vault…",
+        }
+        .fmt(f)
+    }
+}
+
+pub struct ExportPage {
+    format: ExportFormat,
+    entries: Vec<ClockodeEntry>,
+    selected: HashSet<Uuid>,
+    vault_inputs: VaultInputs,
+    /// Shown before any plaintext export, asks for the master password again
+    confirmation: Option<Confirmation>,
+}
+
+/// State of the plaintext export confirmation
+#[derive(Default)]
+struct Confirmation {
+    password: String,
+    verifying: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Go back a screen
+    Back,
+    /// Callback after pressing a [`Hotkey`] of this page
+    Hotkey(Hotkey),
+    /// Select or deselect the entry with the given [`Uuid`]
+    ToggleEntry(Uuid, bool),
+    /// Select every entry
+    SelectAll,
+    /// Deselect every entry
+    SelectNone,
+    /// Update the password of the exported vault
+    UpdatePassword(String),
+    /// Update the repeated password of the exported vault
+    UpdateRepeatPassword(String),
+    /// Open the File Dialog to select the key file of the exported vault
+    OpenKeyFileDialog,
+    /// Key File Selected Callback (after dialog)
+    KeyFileSelected(Option<FileHandle>),
+    /// Stop using the selected key file
+    ClearKeyFile,
+    /// Export the selected entries
+    Submit,
+    /// Update the master password typed on the plaintext export confirmation
+    UpdateConfirmationPassword(String),
+    /// Close the plaintext export confirmation without exporting
+    CancelConfirmation,
+    /// Accept the plaintext export confirmation
+    Confirm,
+    /// Callback after checking the master password typed on the confirmation
+    PasswordVerified(Result<bool, anywho::Error>),
+    /// Export Path Selected Callback (after dialog)
+    ExportPathSelected(Option<FileHandle>),
+}
+
+pub enum Action {
+    /// Does nothing
+    None,
+    /// Go back a screen
+    Back,
+    // Ask parent to run an [`iced::Task`]
+    Run(Task<Message>),
+    /// Add a new [`Toast`] to show
+    AddToast(Toast),
+    /// Ask parent to check the given master password, answering with [`Message::PasswordVerified`]
+    VerifyPassword(SecretString),
+    /// Ask parent to export the given entries to the given filepath
+    ExportContent(PathBuf, Vec<Uuid>),
+    /// Ask parent to show the given entries as Google Authenticator migration codes
+    ShowMigrationCodes(Vec<Uuid>),
+    /// Ask parent to write a printable paper backup of the given entries to the given filepath
+    ExportPaperBackup(PathBuf, Vec<Uuid>),
+    /// Ask parent to write the given entries into a new KeePass vault
+    ExportKeePassVault {
+        path: PathBuf,
+        entry_ids: Vec<Uuid>,
+        password: Option<SecretString>,
+        key_file: Option<PathBuf>,
+    },
+}
+
+impl ExportPage {
+    pub fn new(format: ExportFormat, entries: Vec<ClockodeEntry>) -> (Self, Task<Message>) {
+        // Everything starts selected, exporting the whole vault is the most common case
+        let selected = entries.iter().filter_map(|e| e.id).collect();
+
+        (
+            Self {
+                format,
+                entries,
+                selected,
+                vault_inputs: VaultInputs::default(),
+                confirmation: None,
+            },
+            Task::none(),
+        )
+    }
+
+    pub fn view(&self, _now: Instant) -> iced::Element<'_, Message> {
+        let header = header_view(self.format);
+        let content = selection_view(
+            self.format,
+            &self.entries,
+            &self.selected,
+            &self.vault_inputs,
+        );
+
+        let page = container(
+            container(column![header, content])
+                .padding(5.)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .center(Length::Fill);
+
+        match &self.confirmation {
+            Some(confirmation) => stack![page, confirmation_view(confirmation)].into(),
+            None => page.into(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message, _now: Instant) -> Action {
+        match message {
+            Message::Back => Action::Back,
+            Message::Hotkey(hotkey) => match hotkey {
+                Hotkey::Tab(modifiers) => {
+                    if modifiers.shift() {
+                        Action::Run(focus_previous())
+                    } else {
+                        Action::Run(focus_next())
+                    }
+                }
+                Hotkey::Esc => {
+                    if self.confirmation.take().is_some() {
+                        Action::None
+                    } else {
+                        Action::Back
+                    }
+                }
+            },
+            Message::ToggleEntry(id, checked) => {
+                if checked {
+                    self.selected.insert(id);
+                } else {
+                    self.selected.remove(&id);
+                }
+                Action::None
+            }
+            Message::SelectAll => {
+                self.selected = self.entries.iter().filter_map(|e| e.id).collect();
+                Action::None
+            }
+            Message::SelectNone => {
+                self.selected.clear();
+                Action::None
+            }
+            Message::UpdatePassword(v) => {
+                self.vault_inputs.password = v;
+                Action::None
+            }
+            Message::UpdateRepeatPassword(v) => {
+                self.vault_inputs.repeat_password = v;
+                Action::None
+            }
+            Message::OpenKeyFileDialog => Action::Run(Task::perform(
+                async move {
+                    AsyncFileDialog::new()
+                        .set_directory(dirs::home_dir().unwrap_or("/".into()))
+                        .pick_file()
+                        .await
+                },
+                Message::KeyFileSelected,
+            )),
+            Message::KeyFileSelected(handle) => {
+                if let Some(file_handle) = handle {
+                    self.vault_inputs.key_file = Some(file_handle.path().to_path_buf());
+                }
+                Action::None
+            }
+            Message::ClearKeyFile => {
+                self.vault_inputs.key_file = None;
+                Action::None
+            }
+            Message::Submit => {
+                if self.selected.is_empty() {
+                    return Action::AddToast(Toast::warning_toast("No entries selected"));
+                }
+
+                if self.format.is_plaintext() {
+                    self.confirmation = Some(Confirmation::default());
+                    return Action::None;
+                }
+
+                self.start_export()
+            }
+            Message::UpdateConfirmationPassword(v) => {
+                if let Some(confirmation) = &mut self.confirmation {
+                    confirmation.password = v;
+                }
+                Action::None
+            }
+            Message::CancelConfirmation => {
+                self.confirmation = None;
+                Action::None
+            }
+            Message::Confirm => {
+                let Some(confirmation) = &mut self.confirmation else {
+                    return Action::None;
+                };
+
+                if confirmation.password.is_empty() || confirmation.verifying {
+                    return Action::None;
+                }
+
+                confirmation.verifying = true;
+                Action::VerifyPassword(confirmation.password.clone().into())
+            }
+            Message::PasswordVerified(result) => match result {
+                Ok(true) => {
+                    self.confirmation = None;
+                    self.start_export()
+                }
+                Ok(false) => {
+                    if let Some(confirmation) = &mut self.confirmation {
+                        confirmation.password.clear();
+                        confirmation.verifying = false;
+                    }
+                    Action::AddToast(Toast::error_toast("Incorrect Password"))
+                }
+                Err(err) => {
+                    if let Some(confirmation) = &mut self.confirmation {
+                        confirmation.verifying = false;
+                    }
+                    Action::AddToast(Toast::error_toast(err))
+                }
+            },
+            Message::ExportPathSelected(handle) => {
+                let Some(file_handle) = handle else {
+                    return Action::None;
+                };
+                let path = file_handle.path().to_path_buf();
+
+                match self.format {
+                    ExportFormat::UriList => Action::ExportContent(path, self.selected_ids()),
+                    ExportFormat::PaperBackup => {
+                        Action::ExportPaperBackup(path, self.selected_ids())
+                    }
+                    ExportFormat::KeePassVault => Action::ExportKeePassVault {
+                        path,
+                        entry_ids: self.selected_ids(),
+                        password: (!self.vault_inputs.password.is_empty())
+                            .then(|| self.vault_inputs.password.clone().into()),
+                        key_file: self.vault_inputs.key_file.clone(),
+                    },
+                    ExportFormat::GoogleAuthenticator => Action::None,
+                }
+            }
+        }
+    }
+
+    pub fn subscription(&self, _now: Instant) -> Subscription<Message> {
+        event::listen_with(handle_event)
+    }
+
+    /// Continues the export once every confirmation has been given,
+    /// either opening the save dialog or asking the parent to show the codes
+    fn start_export(&self) -> Action {
+        match self.format {
+            ExportFormat::UriList => Action::Run(Task::perform(
+                async move {
+                    AsyncFileDialog::new()
+                        .set_file_name("export.txt")
+                        .set_directory(dirs::download_dir().unwrap_or("/".into()))
+                        .save_file()
+                        .await
+                },
+                Message::ExportPathSelected,
+            )),
+            ExportFormat::GoogleAuthenticator => Action::ShowMigrationCodes(self.selected_ids()),
+            ExportFormat::PaperBackup => Action::Run(Task::perform(
+                async move {
+                    AsyncFileDialog::new()
+                        .add_filter("html", &["html"])
+                        .set_file_name("clockode-paper-backup.html")
+                        .set_directory(dirs::download_dir().unwrap_or("/".into()))
+                        .save_file()
+                        .await
+                },
+                Message::ExportPathSelected,
+            )),
+            ExportFormat::KeePassVault => {
+                if !self.vault_inputs.valid() {
+                    return Action::AddToast(Toast::warning_toast(
+                        "Set a password or a key file for the new vault",
+                    ));
+                }
+
+                Action::Run(Task::perform(
+                    async move {
+                        AsyncFileDialog::new()
+                            .add_filter("KeePass Vault", &["kdbx"])
+                            .set_file_name("clockode-export.kdbx")
+                            .set_directory(dirs::download_dir().unwrap_or("/".into()))
+                            .save_file()
+                            .await
+                    },
+                    Message::ExportPathSelected,
+                ))
+            }
+        }
+    }
+
+    /// Ids of the selected entries, in the same order they are listed
+    fn selected_ids(&self) -> Vec<Uuid> {
+        self.entries
+            .iter()
+            .filter_map(|e| e.id)
+            .filter(|id| self.selected.contains(id))
+            .collect()
+    }
+}
+
+/// Holds the state for the credential inputs of an exported KeePass vault
+#[derive(Default)]
+struct VaultInputs {
+    password: String,
+    repeat_password: String,
+    key_file: Option<PathBuf>,
+}
+
+impl VaultInputs {
+    /// Returns true if the new vault has a usable password and/or key file
+    fn valid(&self) -> bool {
+        let password_valid = self.password.eq(&self.repeat_password)
+            && (self.password.is_empty() || self.password.len() > 3);
+
+        password_valid && (!self.password.is_empty() || self.key_file.is_some())
+    }
+}
+
+/// View of the header of this screen
+fn header_view<'a>(format: ExportFormat) -> Element<'a, Message> {
+    row![
+        // Back button
+        button(
+            row![
+                icons::get_icon("go-previous-symbolic", 21),
+                text("Back").size(style::font_size::BODY)
+            ]
+            .spacing(style::spacing::TINY)
+            .align_y(iced::Alignment::Center)
+        )
+        .on_press(Message::Back)
+        .padding(8)
+        .style(style::secondary_button),
+        column![
+            text("Export").size(style::font_size::TITLE),
+            text(format.to_string())
+                .size(style::font_size::SMALL)
+                .style(style::muted_text),
+        ]
+        .spacing(style::spacing::TINY),
+        space().width(Length::Fill),
+        row![
+            button(text("Select All").size(style::font_size::BODY))
+                .on_press(Message::SelectAll)
+                .padding(8)
+                .style(style::secondary_button),
+            button(text("Select None").size(style::font_size::BODY))
+                .on_press(Message::SelectNone)
+                .padding(8)
+                .style(style::secondary_button),
+        ]
+        .spacing(style::spacing::SMALL)
+    ]
+    .spacing(style::spacing::LARGE)
+    .padding(10)
+    .align_y(iced::Alignment::Center)
+    .width(Length::Fill)
+    .into()
+}
+
+fn selection_view<'a>(
+    format: ExportFormat,
+    entries: &'a [ClockodeEntry],
+    selected: &'a HashSet<Uuid>,
+    vault_inputs: &'a VaultInputs,
+) -> Element<'a, Message> {
+    let entries_list = entries.iter().fold(
+        Column::new().spacing(style::spacing::SMALL),
+        |col, entry| {
+            let Some(id) = entry.id else {
+                return col;
+            };
+
+            let label = match entry.totp.issuer.as_deref() {
+                Some(issuer) if !issuer.is_empty() && issuer != entry.name => {
+                    format!("{} ({})", entry.name, issuer)
+                }
+                _ => entry.name.clone(),
+            };
+
+            col.push(
+                container(
+                    checkbox(selected.contains(&id))
+                        .label(label)
+                        .on_toggle(move |checked| Message::ToggleEntry(id, checked))
+                        .size(style::font_size::LARGE)
+                        .text_size(style::font_size::MEDIUM)
+                        .width(Length::Fill),
+                )
+                .padding(12)
+                .style(style::entry_card),
+            )
+        },
+    );
+
+    // selected entries Google Authenticator would show wrong codes for, they are left out
+    let left_out: Vec<&str> = if format == ExportFormat::GoogleAuthenticator {
+        entries
+            .iter()
+            .filter(|entry| entry.id.is_some_and(|id| selected.contains(&id)))
+            .filter(|entry| !is_migration_compatible(entry))
+            .map(|entry| entry.name.as_str())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let left_out_notice = (!left_out.is_empty()).then(|| {
+        text(format!(
+            "Not included, Google Authenticator only supports 30s codes with 6 or 8 digits: {}",
+            left_out.join(", ")
+        ))
+        .size(style::font_size::SMALL)
+        .style(style::label_text)
+    });
+
+    let can_submit = selected.len() > left_out.len()
+        && (format != ExportFormat::KeePassVault || vault_inputs.valid());
+
+    let form = column![
+        entries_list,
+        (format == ExportFormat::KeePassVault).then(|| vault_credentials_view(vault_inputs)),
+        left_out_notice,
+        button(
+            text(format!(
+                "Export {} {}",
+                selected.len(),
+                if selected.len() == 1 {
+                    "Entry"
+                } else {
+                    "Entries"
+                }
+            ))
+            .size(style::font_size::MEDIUM)
+            .width(Length::Fill)
+            .align_x(Alignment::Center)
+        )
+        .on_press_maybe(can_submit.then_some(Message::Submit))
+        .padding(16)
+        .width(Length::Fill)
+        .style(style::primary_submit_button),
+    ]
+    .spacing(style::spacing::XLARGE)
+    .padding(10)
+    .width(Length::Fill.max(600));
+
+    scrollable(container(form).center_x(Length::Fill))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+/// Modal explaining the risk of a plaintext export and asking for the master password again
+fn confirmation_view<'a>(confirmation: &'a Confirmation) -> Element<'a, Message> {
+    let can_confirm = !confirmation.password.is_empty() && !confirmation.verifying;
+
+    let dialog = container(
+        column![
+            text("Export Without Encryption?").size(style::font_size::TITLE),
+            text(
+                "The exported data will contain every selected secret in plain text. \
+                 Anyone who can read it, or any program, backup or sync service with access \
+                 to its location, will be able to generate your codes."
+            )
+            .size(style::font_size::BODY),
+            text("Delete it as soon as you no longer need it.")
+                .size(style::font_size::BODY)
+                .style(style::muted_text),
+            column![
+                text("Master Password")
+                    .size(style::font_size::BODY)
+                    .style(style::label_text),
+                text_input("Enter your password to continue", &confirmation.password)
+                    .secure(true)
+                    .on_input(Message::UpdateConfirmationPassword)
+                    .on_submit_maybe(can_confirm.then_some(Message::Confirm))
+                    .padding(12)
+                    .size(style::font_size::MEDIUM)
+            ]
+            .spacing(style::spacing::TINY),
+            row![
+                button(text("Cancel").size(style::font_size::BODY))
+                    .on_press(Message::CancelConfirmation)
+                    .padding(8)
+                    .style(style::secondary_button),
+                space().width(Length::Fill),
+                button(text("Export Unencrypted").size(style::font_size::BODY))
+                    .on_press_maybe(can_confirm.then_some(Message::Confirm))
+                    .padding(8)
+                    .style(style::danger_button),
+            ]
+            .align_y(Alignment::Center),
+        ]
+        .spacing(style::spacing::MEDIUM)
+        .padding(24)
+        .width(Length::Fill.max(500)),
+    )
+    .style(style::card_container);
+
+    container(dialog).padding(20).center(Length::Fill).into()
+}
+
+/// Password and key file inputs of the exported KeePass vault
+fn vault_credentials_view<'a>(vault_inputs: &'a VaultInputs) -> Element<'a, Message> {
+    let key_file_label = vault_inputs
+        .key_file
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| String::from("No key file"));
+
+    container(
+        column![
+            text("New Vault Credentials").size(style::font_size::MEDIUM),
+            column![
+                text("Password")
+                    .size(style::font_size::BODY)
+                    .style(style::label_text),
+                text_input("Password of the new vault", &vault_inputs.password)
+                    .secure(true)
+                    .on_input(Message::UpdatePassword)
+                    .padding(12)
+                    .size(style::font_size::MEDIUM)
+            ]
+            .spacing(style::spacing::TINY),
+            column![
+                text("Confirm Password")
+                    .size(style::font_size::BODY)
+                    .style(style::label_text),
+                text_input("Re-enter the password", &vault_inputs.repeat_password)
+                    .secure(true)
+                    .on_input(Message::UpdateRepeatPassword)
+                    .padding(12)
+                    .size(style::font_size::MEDIUM)
+            ]
+            .spacing(style::spacing::TINY),
+            column![
+                text("Key File (Optional)")
+                    .size(style::font_size::BODY)
+                    .style(style::label_text),
+                row![
+                    text(key_file_label)
+                        .size(style::font_size::MEDIUM)
+                        .width(Length::Fill),
+                    button(text("Choose…").size(style::font_size::BODY))
+                        .on_press(Message::OpenKeyFileDialog)
+                        .padding(8)
+                        .style(style::secondary_button),
+                    button(text("Clear").size(style::font_size::BODY))
+                        .on_press_maybe(
+                            vault_inputs.key_file.is_some().then_some(Message::ClearKeyFile)
+                        )
+                        .padding(8)
+                        .style(style::secondary_button),
+                ]
+                .spacing(style::spacing::SMALL)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(style::spacing::TINY),
+            text("Anyone with these credentials will be able to read the exported entries.")
+                .size(style::font_size::SMALL)
+                .style(style::muted_text),
+        ]
+        .spacing(style::spacing::MEDIUM),
+    )
+    .padding(16)
+    .style(style::entry_card)
+    .into()
+}
+
+//
+// SUBSCRIPTIONS
+//
+
+#[derive(Debug, Clone)]
+pub enum Hotkey {
+    Tab(Modifiers),
+    Esc,
+}
+
+fn handle_event(event: event::Event, _: event::Status, _: iced::window::Id) -> Option<Message> {
+    #[allow(clippy::collapsible_match)]
+    match event {
+        event::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => match key {
+            Key::Named(Named::Tab) => Some(Message::Hotkey(Hotkey::Tab(modifiers))),
+            Key::Named(Named::Escape) => Some(Message::Hotkey(Hotkey::Esc)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::PathBuf;
+
+use iced::{
+    Alignment, Element,
+    Length::{self},
+    Subscription, Task, event,
+    keyboard::{self, Key, key::Named},
+    time::Instant,
+    widget::{button, column, container, image, row, space, text},
+};
+use rfd::{AsyncFileDialog, FileHandle};
+use tracing::error;
+
+use clockode_core::{MigrationCodes, render_qr_png, write_private_file};
+
+use crate::{
+    app::{utils::style, widgets::Toast},
+    icons,
+};
+
+/// Pixel size used when rendering the migration codes, both on screen and when saved
+const QR_SIZE: u32 = 800;
+
+pub struct MigrationPage {
+    /// Rendered PNG bytes of every migration batch, in order
+    codes: Vec<Vec<u8>>,
+    /// Image handles of the rendered codes, built once so they aren't decoded on every render
+    handles: Vec<image::Handle>,
+    /// Names of the entries Google Authenticator can't generate codes for, left out
+    skipped: Vec<String>,
+    current: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Go back a screen
+    Back,
+    /// Callback after pressing a [`Hotkey`] of this page
+    Hotkey(Hotkey),
+    /// Show the previous code
+    Previous,
+    /// Show the next code
+    Next,
+    /// Open the dialog to select where to save the codes
+    OpenSaveDialog,
+    /// Callback after selecting the folder where the codes will be saved
+    SaveFolderSelected(Option<FileHandle>),
+    /// Callback after saving the codes, with the folder and the number of written files
+    CodesSaved(Result<(PathBuf, usize), anywho::Error>),
+}
+
+pub enum Action {
+    /// Does nothing
+    None,
+    /// Go back a screen
+    Back,
+    // Ask parent to run an [`iced::Task`]
+    Run(Task<Message>),
+    /// Add a new [`Toast`] to show
+    AddToast(Toast),
+}
+
+impl MigrationPage {
+    pub fn new(migration: MigrationCodes) -> Result<(Self, Task<Message>), anywho::Error> {
+        let codes = migration
+            .uris
+            .iter()
+            .map(|uri| render_qr_png(uri, QR_SIZE))
+            .collect::<Result<Vec<_>, _>>()?;
+        let handles = codes
+            .iter()
+            .map(|bytes| image::Handle::from_bytes(bytes.clone()))
+            .collect();
+
+        Ok((
+            Self {
+                codes,
+                handles,
+                skipped: migration.skipped,
+                current: 0,
+            },
+            Task::none(),
+        ))
+    }
+
+    pub fn view(&self, _now: Instant) -> iced::Element<'_, Message> {
+        let header = header_view(self.current, self.codes.len());
+
+        let code: Element<Message> = match self.handles.get(self.current) {
+            Some(handle) => image(handle.clone())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .content_fit(iced::ContentFit::Contain)
+                .into(),
+            None => text("No codes to show").into(),
+        };
+
+        let navigation = row![
+            button(text("Previous").size(style::font_size::BODY))
+                .on_press_maybe((self.current > 0).then_some(Message::Previous))
+                .padding(8)
+                .style(style::secondary_button),
+            text(format!("{} / {}", self.current + 1, self.codes.len()))
+                .size(style::font_size::MEDIUM),
+            button(text("Next").size(style::font_size::BODY))
+                .on_press_maybe((self.current + 1 < self.codes.len()).then_some(Message::Next))
+                .padding(8)
+                .style(style::secondary_button),
+        ]
+        .spacing(style::spacing::LARGE)
+        .align_y(Alignment::Center);
+
+        // the left out entries would show wrong codes in Google Authenticator, say which ones
+        let skipped_notice = (!self.skipped.is_empty()).then(|| {
+            container(
+                text(format!(
+                    "Not included, Google Authenticator only supports 30s codes with 6 or 8 digits: {}",
+                    self.skipped.join(", ")
+                ))
+                .size(style::font_size::SMALL)
+                .style(style::label_text),
+            )
+            .padding([0, 10])
+            .into()
+        });
+
+        container(
+            column![header]
+                .extend(skipped_notice)
+                .push(container(code).padding(10).center(Length::Fill))
+                .push(container(navigation).center_x(Length::Fill).padding(10))
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .padding(5.)
+        .center(Length::Fill)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message, now: Instant) -> Action {
+        match message {
+            Message::Back => Action::Back,
+            Message::Hotkey(hotkey) => match hotkey {
+                Hotkey::Esc => Action::Back,
+                Hotkey::Left => self.update(Message::Previous, now),
+                Hotkey::Right => self.update(Message::Next, now),
+            },
+            Message::Previous => {
+                self.current = self.current.saturating_sub(1);
+                Action::None
+            }
+            Message::Next => {
+                if self.current + 1 < self.codes.len() {
+                    self.current += 1;
+                }
+                Action::None
+            }
+            Message::OpenSaveDialog => Action::Run(Task::perform(
+                async move {
+                    AsyncFileDialog::new()
+                        .set_directory(dirs::download_dir().unwrap_or("/".into()))
+                        .pick_folder()
+                        .await
+                },
+                Message::SaveFolderSelected,
+            )),
+            Message::SaveFolderSelected(handle) => {
+                if let Some(folder_handle) = handle {
+                    let folder = folder_handle.path().to_path_buf();
+                    let codes = self.codes.clone();
+                    return Action::Run(Task::perform(
+                        async move { smol::unblock(move || save_codes(folder, codes)).await },
+                        Message::CodesSaved,
+                    ));
+                }
+                Action::None
+            }
+            Message::CodesSaved(result) => match result {
+                Ok((folder, count)) => Action::AddToast(Toast::warning_toast(format!(
+                    "Saved {count} migration {} to {}. They are NOT encrypted, delete them once imported.",
+                    if count == 1 { "code" } else { "codes" },
+                    folder.display()
+                ))),
+                Err(err) => {
+                    error!("{err}");
+                    Action::AddToast(Toast::error_toast(err))
+                }
+            },
+        }
+    }
+
+    pub fn subscription(&self, _now: Instant) -> Subscription<Message> {
+        event::listen_with(handle_event)
+    }
+}
+
+/// Writes every code as `clockode-migration-<index>-of-<total>.png` inside `folder`
+fn save_codes(folder: PathBuf, codes: Vec<Vec<u8>>) -> Result<(PathBuf, usize), anywho::Error> {
+    let total = codes.len();
+    for (index, bytes) in codes.iter().enumerate() {
+        let path = folder.join(format!("clockode-migration-{}-of-{}.png", index + 1, total));
+        write_private_file(&path, bytes)?;
+    }
+    Ok((folder, total))
+}
+
+/// View of the header of this screen
+fn header_view<'a>(current: usize, total: usize) -> Element<'a, Message> {
+    row![
+        // Back button
+        button(
+            row![
+                icons::get_icon("go-previous-symbolic", 21),
+                text("Back").size(style::font_size::BODY)
+            ]
+            .spacing(style::spacing::TINY)
+            .align_y(iced::Alignment::Center)
+        )
+        .on_press(Message::Back)
+        .padding(8)
+        .style(style::secondary_button),
+        column![
+            text("Transfer to Google Authenticator").size(style::font_size::TITLE),
+            text(format!(
+                "Scan code {} of {} with \"Import accounts\" on your phone",
+                current + 1,
+                total
+            ))
+            .size(style::font_size::SMALL)
+            .style(style::muted_text),
+        ]
+        .spacing(style::spacing::TINY),
+        space().width(Length::Fill),
+        button(
+            row![
+                icons::get_icon("document-export-symbolic", 21).style(|theme, _status| {
+                    let primary_style =
+                        button::primary(theme, iced::widget::button::Status::Active);
+                    iced::widget::svg::Style {
+                        color: Some(primary_style.text_color),
+                    }
+                }),
+                text("Save as PNG").size(style::font_size::BODY)
+            ]
+            .spacing(style::spacing::TINY)
+            .align_y(iced::Alignment::Center)
+        )
+        .on_press(Message::OpenSaveDialog)
+        .padding(8)
+        .style(style::primary_button),
+    ]
+    .spacing(style::spacing::LARGE)
+    .padding(10)
+    .align_y(iced::Alignment::Center)
+    .width(Length::Fill)
+    .into()
+}
+
+//
+// SUBSCRIPTIONS
+//
+
+#[derive(Debug, Clone)]
+pub enum Hotkey {
+    Esc,
+    Left,
+    Right,
+}
+
+fn handle_event(event: event::Event, _: event::Status, _: iced::window::Id) -> Option<Message> {
+    #[allow(clippy::collapsible_match)]
+    match event {
+        event::Event::Keyboard(keyboard::Event::KeyPressed {
+            key, modifiers: _, ..
+        }) => match key {
+            Key::Named(Named::Escape) => Some(Message::Hotkey(Hotkey::Esc)),
+            Key::Named(Named::ArrowLeft) => Some(Message::Hotkey(Hotkey::Left)),
+            Key::Named(Named::ArrowRight) => Some(Message::Hotkey(Hotkey::Right)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
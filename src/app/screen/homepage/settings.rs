@@ -16,15 +16,24 @@ use iced::{
 use rfd::{AsyncFileDialog, FileHandle};
 use tracing::error;
 
+use super::export::ExportFormat;
 use crate::{
     APP_ID,
     app::{utils::style, widgets::Toast},
-    config::{ColockodeTheme, Config},
+    config::{ColockodeTheme, Config, SharedVault},
     icons,
 };
 
+/// Choices for how many of the latest backups to keep
+const BACKUPS_KEPT: &[usize] = &[0, 5, 10, 20, 50];
+/// Choices for how many days of daily backups to keep
+const BACKUP_DAYS: &[u32] = &[0, 7, 14, 30];
+
 pub struct SettingsPage {
     config: Arc<Mutex<Config>>,
+    export_format: ExportFormat,
+    /// The vault is open read-only, importing is not possible
+    read_only: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -37,16 +46,42 @@ pub enum Message {
     ChangedTheme(ColockodeTheme),
     /// Configuration Saved
     ConfigurationSaved(Result<(), anywho::Error>),
+    /// Callback after the user changes the export format
+    ChangedExportFormat(ExportFormat),
     /// Open the File Dialog to select a file to import
     OpenImportDialog,
-    /// Open the File Dialog to select where to export the file
-    OpenExportDialog,
+    /// Ask to export the vault content with the selected [`ExportFormat`]
+    Export,
     /// Import Path Selected Callback (after dialog)
     ImportPathSelected(Option<FileHandle>),
-    /// Export Path Selected Callback (after dialog)
-    ExportPathSelected(Option<FileHandle>),
     /// Opens the given URL in the browser
     LaunchUrl(String),
+    /// Open the File Dialog to select a vault to show next to the personal one
+    OpenSharedVaultDialog,
+    /// Callback after selecting a shared vault (after dialog)
+    SharedVaultSelected(Option<FileHandle>),
+    /// Stop showing the shared vault at the given path
+    RemoveSharedVault(PathBuf),
+    /// Callback after toggling whether the shared vault at the given path can be changed
+    ToggledSharedVaultWrites(PathBuf, bool),
+    /// Callback after the user changes how many of the latest backups are kept
+    ChangedBackupsKept(usize),
+    /// Callback after the user changes for how many days daily backups are kept
+    ChangedBackupDays(u32),
+    /// Ask to open the list of backups to restore one
+    OpenBackups,
+    /// Callback after toggling whether other programs can request codes over the IPC socket
+    #[cfg(unix)]
+    ToggledIpc(bool),
+    /// Forget every program that was allowed to always request codes
+    #[cfg(unix)]
+    ForgetIpcClients,
+    /// Callback after toggling the D-Bus service
+    #[cfg(unix)]
+    ToggledDbus(bool),
+    /// Callback after toggling whether later launches raise the open window
+    #[cfg(unix)]
+    ToggledSingleInstance(bool),
 }
 
 pub enum Action {
@@ -60,18 +95,27 @@ pub enum Action {
     AddToast(Toast),
     /// Ask parent to import some content from the given filepath
     ImportContent(PathBuf),
-    /// Ask parent to export the context to the given filepath
-    ExportContent(PathBuf),
+    /// Ask parent to start exporting the content with the given [`ExportFormat`]
+    OpenExport(ExportFormat),
+    /// Ask parent to open the list of backups
+    OpenBackups,
 }
 
 impl SettingsPage {
-    pub fn new(config: Arc<Mutex<Config>>) -> (Self, Task<Message>) {
-        (Self { config }, Task::none())
+    pub fn new(config: Arc<Mutex<Config>>, read_only: bool) -> (Self, Task<Message>) {
+        (
+            Self {
+                config,
+                export_format: ExportFormat::default(),
+                read_only,
+            },
+            Task::none(),
+        )
     }
 
     pub fn view(&self, _now: Instant) -> iced::Element<'_, Message> {
         let header = header_view();
-        let content = settings_view(&self.config);
+        let content = settings_view(&self.config, self.export_format, self.read_only);
 
         container(
             container(column![header, content])
@@ -107,6 +151,10 @@ impl SettingsPage {
                 Ok(_) => Action::None,
                 Err(e) => Action::AddToast(Toast::error_toast(e)),
             },
+            Message::ChangedExportFormat(export_format) => {
+                self.export_format = export_format;
+                Action::None
+            }
             Message::OpenImportDialog => Action::Run(Task::perform(
                 async move {
                     AsyncFileDialog::new()
@@ -117,28 +165,13 @@ impl SettingsPage {
                 },
                 Message::ImportPathSelected,
             )),
-            Message::OpenExportDialog => Action::Run(Task::perform(
-                async move {
-                    AsyncFileDialog::new()
-                        .set_file_name("export.txt")
-                        .set_directory(dirs::download_dir().unwrap_or("/".into()))
-                        .save_file()
-                        .await
-                },
-                Message::ExportPathSelected,
-            )),
+            Message::Export => Action::OpenExport(self.export_format),
             Message::ImportPathSelected(handle) => {
                 if let Some(file_handle) = handle {
                     return Action::ImportContent(file_handle.path().to_path_buf());
                 }
                 Action::None
             }
-            Message::ExportPathSelected(handle) => {
-                if let Some(file_handle) = handle {
-                    return Action::ExportContent(file_handle.path().to_path_buf());
-                }
-                Action::None
-            }
             Message::LaunchUrl(url) => {
                 match open::that_detached(&url) {
                     Ok(()) => {}
@@ -148,7 +181,72 @@ impl SettingsPage {
                 }
                 Action::None
             }
+            Message::OpenSharedVaultDialog => Action::Run(Task::perform(
+                async move {
+                    AsyncFileDialog::new()
+                        .add_filter("KeePass Vault", &["kdbx"])
+                        .pick_file()
+                        .await
+                },
+                Message::SharedVaultSelected,
+            )),
+            Message::SharedVaultSelected(handle) => {
+                let Some(file_handle) = handle else {
+                    return Action::None;
+                };
+
+                let path = file_handle.path().to_path_buf();
+                self.save_config(|cfg| {
+                    if !cfg.shared_vaults.iter().any(|vault| vault.path == path) {
+                        cfg.shared_vaults.push(SharedVault {
+                            path,
+                            writable: false,
+                        });
+                    }
+                })
+            }
+            Message::RemoveSharedVault(path) => {
+                self.save_config(|cfg| cfg.shared_vaults.retain(|vault| vault.path != path))
+            }
+            Message::ToggledSharedVaultWrites(path, writable) => self.save_config(|cfg| {
+                if let Some(vault) = cfg
+                    .shared_vaults
+                    .iter_mut()
+                    .find(|vault| vault.path == path)
+                {
+                    vault.writable = writable;
+                }
+            }),
+            Message::ChangedBackupsKept(kept) => self.save_config(|cfg| cfg.backups_kept = kept),
+            Message::ChangedBackupDays(days) => self.save_config(|cfg| cfg.backup_days = days),
+            Message::OpenBackups => Action::OpenBackups,
+            #[cfg(unix)]
+            Message::ToggledIpc(enabled) => self.save_config(|cfg| cfg.ipc_enabled = enabled),
+            #[cfg(unix)]
+            Message::ForgetIpcClients => self.save_config(|cfg| cfg.ipc_trusted_programs.clear()),
+            #[cfg(unix)]
+            Message::ToggledDbus(enabled) => self.save_config(|cfg| cfg.dbus_enabled = enabled),
+            #[cfg(unix)]
+            Message::ToggledSingleInstance(enabled) => {
+                self.save_config(|cfg| cfg.single_instance = enabled)
+            }
+        }
+    }
+
+    /// Applies the given change to the config and saves it
+    fn save_config(&self, change: impl FnOnce(&mut Config)) -> Action {
+        if let Ok(mut cfg) = self.config.lock() {
+            change(&mut cfg);
+            let cfg_clone = cfg.clone();
+
+            return Action::Run(Task::perform(
+                async move { cfg_clone.save(APP_ID).await },
+                Message::ConfigurationSaved,
+            ));
+        } else {
+            error!("Warning: config mutex poisoned. Cannot save the config.");
         }
+        Action::None
     }
 
     pub fn subscription(&self, _now: Instant) -> Subscription<Message> {
@@ -187,13 +285,22 @@ fn header_view<'a>() -> Element<'a, Message> {
     .into()
 }
 
-fn settings_view<'a>(config: &'a Arc<Mutex<Config>>) -> Element<'a, Message> {
+fn settings_view<'a>(
+    config: &'a Arc<Mutex<Config>>,
+    export_format: ExportFormat,
+    read_only: bool,
+) -> Element<'a, Message> {
     let settings_form = column![
         // Export and Import buttons in a row
         column![
             text("Vault Management")
                 .size(style::font_size::BODY)
                 .style(style::label_text),
+            pick_list(Some(export_format), ExportFormat::ALL, |f: &ExportFormat| f
+                .to_string())
+            .on_select(Message::ChangedExportFormat)
+            .width(Length::Fill)
+            .padding(12),
             row![
                 button(
                     row![
@@ -209,7 +316,7 @@ fn settings_view<'a>(config: &'a Arc<Mutex<Config>>) -> Element<'a, Message> {
                     .spacing(style::spacing::TINY)
                     .align_y(Alignment::Center)
                 )
-                .on_press(Message::OpenExportDialog)
+                .on_press(Message::Export)
                 .padding(12)
                 .width(Length::Fill)
                 .style(style::primary_button),
@@ -227,7 +334,7 @@ fn settings_view<'a>(config: &'a Arc<Mutex<Config>>) -> Element<'a, Message> {
                     .spacing(style::spacing::TINY)
                     .align_y(Alignment::Center)
                 )
-                .on_press(Message::OpenImportDialog)
+                .on_press_maybe((!read_only).then_some(Message::OpenImportDialog))
                 .padding(12)
                 .width(Length::Fill)
                 .style(style::primary_button),
@@ -255,6 +362,9 @@ fn settings_view<'a>(config: &'a Arc<Mutex<Config>>) -> Element<'a, Message> {
         ]
         .spacing(style::spacing::TINY),
     ]
+    .push(backups_view(config, read_only))
+    .push(shared_vaults_view(config))
+    .extend(integration_settings_view(config))
     .spacing(style::spacing::XLARGE)
     .padding(10)
     .width(Length::Fill.max(600));
@@ -306,6 +416,160 @@ fn settings_view<'a>(config: &'a Arc<Mutex<Config>>) -> Element<'a, Message> {
     .into()
 }
 
+/// How many backups are kept, with the button to restore one (disabled on read-only vaults)
+fn backups_view<'a>(config: &'a Arc<Mutex<Config>>, read_only: bool) -> Element<'a, Message> {
+    let (kept, days) = config
+        .lock()
+        .map(|cfg| (cfg.backups_kept, cfg.backup_days))
+        .unwrap_or_default();
+
+    column![
+        text("Backups")
+            .size(style::font_size::BODY)
+            .style(style::label_text),
+        text("A copy of the vault is kept in a backups folder next to it before every change.")
+            .size(style::font_size::SMALL)
+            .style(style::muted_text),
+        row![
+            pick_list(Some(kept), BACKUPS_KEPT, |kept: &usize| match kept {
+                0 => String::from("No recent backups"),
+                kept => format!("Last {kept} changes"),
+            })
+            .on_select(Message::ChangedBackupsKept)
+            .width(Length::Fill)
+            .padding(12),
+            pick_list(Some(days), BACKUP_DAYS, |days: &u32| match days {
+                0 => String::from("No daily backups"),
+                days => format!("Daily for {days} days"),
+            })
+            .on_select(Message::ChangedBackupDays)
+            .width(Length::Fill)
+            .padding(12),
+        ]
+        .spacing(style::spacing::MEDIUM),
+        button(text("Restore a Backup").size(style::font_size::BODY))
+            .on_press_maybe((!read_only).then_some(Message::OpenBackups))
+            .padding(8)
+            .style(style::secondary_button),
+    ]
+    .spacing(style::spacing::SMALL)
+    .into()
+}
+
+/// Vaults shown next to the personal one, with the button to add more
+fn shared_vaults_view<'a>(config: &'a Arc<Mutex<Config>>) -> Element<'a, Message> {
+    let vaults = config
+        .lock()
+        .map(|cfg| cfg.shared_vaults.clone())
+        .unwrap_or_default();
+
+    let list = vaults
+        .into_iter()
+        .fold(column![].spacing(style::spacing::SMALL), |col, vault| {
+            col.push(
+                row![
+                    column![
+                        text(vault.name()).size(style::font_size::BODY),
+                        text(vault.path.display().to_string())
+                            .size(style::font_size::SMALL)
+                            .style(style::muted_text),
+                    ]
+                    .spacing(style::spacing::TINY)
+                    .width(Length::Fill),
+                    iced::widget::checkbox(vault.writable)
+                        .label("Allow changes")
+                        .on_toggle({
+                            let path = vault.path.clone();
+                            move |writable| {
+                                Message::ToggledSharedVaultWrites(path.clone(), writable)
+                            }
+                        }),
+                    button(icons::get_icon("user-trash-full-symbolic", 21))
+                        .on_press(Message::RemoveSharedVault(vault.path))
+                        .padding(8)
+                        .style(style::danger_button),
+                ]
+                .spacing(style::spacing::SMALL)
+                .align_y(Alignment::Center),
+            )
+        });
+
+    column![
+        text("Shared Vaults")
+            .size(style::font_size::BODY)
+            .style(style::label_text),
+        text("Team vaults show next to yours, each unlocked with its own password. They are read-only unless changes are allowed.")
+            .size(style::font_size::SMALL)
+            .style(style::muted_text),
+        list,
+        button(text("Add Shared Vault").size(style::font_size::BODY))
+            .on_press(Message::OpenSharedVaultDialog)
+            .padding(8)
+            .style(style::secondary_button),
+    ]
+    .spacing(style::spacing::SMALL)
+    .into()
+}
+
+/// IPC socket, D-Bus service and single instance toggles, and the programs allowed to always
+/// request codes
+#[cfg(unix)]
+fn integration_settings_view<'a>(config: &'a Arc<Mutex<Config>>) -> Option<Element<'a, Message>> {
+    let (enabled, trusted_clients, dbus_enabled, single_instance) = config
+        .lock()
+        .map(|cfg| {
+            (
+                cfg.ipc_enabled,
+                cfg.ipc_trusted_programs.len(),
+                cfg.dbus_enabled,
+                cfg.single_instance,
+            )
+        })
+        .unwrap_or_default();
+
+    let socket_hint = match crate::app::ipc::socket_path() {
+        Some(path) => format!(
+            "Scripts can ask for codes through {} while the vault is unlocked, every request has to be allowed here first.",
+            path.display()
+        ),
+        None => String::from("No runtime directory is available on this system."),
+    };
+
+    Some(
+        column![
+            text("Integration")
+                .size(style::font_size::BODY)
+                .style(style::label_text),
+            iced::widget::checkbox(enabled)
+                .label("Let other programs request codes")
+                .on_toggle(Message::ToggledIpc),
+            text(socket_hint)
+                .size(style::font_size::SMALL)
+                .style(style::muted_text),
+            button(
+                text(format!("Forget allowed programs ({trusted_clients})"))
+                    .size(style::font_size::BODY)
+            )
+            .on_press_maybe((trusted_clients > 0).then_some(Message::ForgetIpcClients))
+            .padding(8)
+            .style(style::secondary_button),
+            iced::widget::checkbox(dbus_enabled)
+                .label("Let launchers and shell extensions lock the vault and copy codes (D-Bus)")
+                .on_toggle(Message::ToggledDbus),
+            iced::widget::checkbox(single_instance)
+                .label("Bring the open window to the front instead of opening another one")
+                .on_toggle(Message::ToggledSingleInstance),
+        ]
+        .spacing(style::spacing::SMALL)
+        .into(),
+    )
+}
+
+#[cfg(not(unix))]
+fn integration_settings_view<'a>(_config: &'a Arc<Mutex<Config>>) -> Option<Element<'a, Message>> {
+    None
+}
+
 //
 // SUBSCRIPTIONS
 //
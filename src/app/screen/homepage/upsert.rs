@@ -8,13 +8,18 @@ use iced::{
     },
 };
 use rfd::{AsyncFileDialog, FileHandle};
+use std::path::PathBuf;
 use totp_rs::Algorithm;
 use tracing::error;
 
+use clockode_core::{
+    ALL_ALGORITHMS, ClockodeEntry, InputableClockodeEntry, QR_IMAGE_EXTENSIONS, is_qr_image,
+    read_qr_from_file, write_private_file,
+};
+
 use crate::{
     app::{
-        core::ClockodeEntry,
-        utils::{ALL_ALGORITHMS, InputableClockodeEntry, read_qr_from_file, style},
+        utils::{read_qr_from_clipboard, style},
         widgets::Toast,
     },
     icons,
@@ -25,10 +30,53 @@ mod scan_qr;
 
 pub struct UpsertPage {
     entry: InputableClockodeEntry,
+    /// The edited entry as it was read when the page was opened, to detect changes made meanwhile
+    original: Option<ClockodeEntry>,
     show_qr: bool,
+    /// Large PNG of the entry QR code shown over the whole window, rendered once when opened
+    qr_fullscreen: Option<image::Handle>,
+    qr_file_format: QrFileFormat,
+    qr_png_size: u32,
+    /// Entries decoded from an image holding more than one QR code, waiting for the user to pick
+    detected_entries: Option<Vec<InputableClockodeEntry>>,
     subscreen: SubScreen,
 }
 
+/// Sizes the QR code PNG can be saved at
+const QR_PNG_SIZES: &[u32] = &[256, 512, 1024, 2048];
+
+/// Pixel size of the QR code in the full window view
+const QR_FULLSCREEN_SIZE: u32 = 1024;
+
+/// File formats the entry QR code can be saved as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QrFileFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+impl QrFileFormat {
+    pub const ALL: &'static [Self] = &[Self::Png, Self::Svg];
+
+    fn extension(&self) -> &'static str {
+        match self {
+            QrFileFormat::Png => "png",
+            QrFileFormat::Svg => "svg",
+        }
+    }
+}
+
+impl std::fmt::Display for QrFileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QrFileFormat::Png => "PNG",
+            QrFileFormat::Svg => "SVG",
+        }
+        .fmt(f)
+    }
+}
+
 pub enum SubScreen {
     UpsertPage,
     #[cfg(unix)]
@@ -53,9 +101,37 @@ pub enum Message {
     OpenQrFileSelection,
     /// Callback after selecting a QR file
     QrFileSelected(Option<FileHandle>),
+    /// A QR image was dropped on the window (or handed over by the parent)
+    QrFileDropped(PathBuf),
+    /// Callback after decoding the selected or dropped QR image
+    QrFileDecoded(Result<Vec<String>, anywho::Error>),
+    /// `otpauth://` URIs handed over by the parent (command line or link handler)
+    UrisReceived(Vec<String>),
+    /// Decode the QR image currently on the clipboard
+    PasteQrImage,
+    /// Callback after decoding the clipboard image
+    ClipboardQrDecoded(Result<Vec<String>, anywho::Error>),
+    /// Fill the form with the detected entry at the given index
+    ChooseDetectedEntry(usize),
+    /// Create every detected entry at once
+    ImportAllDetectedEntries,
+    /// Close the detected entries chooser without picking any
+    CloseDetectedEntries,
 
     /// Wants to show/hide the current entry qr code
     ToggleShowQRCode,
+    /// Show/hide the current entry qr code over the whole window
+    ToggleQrFullscreen,
+    /// Changed the format the qr code will be saved as
+    ChangedQrFileFormat(QrFileFormat),
+    /// Changed the size the qr code PNG will be rendered at
+    ChangedQrPngSize(u32),
+    /// Open the dialog to select where to save the qr code
+    OpenSaveQrDialog,
+    /// Callback after selecting where to save the qr code
+    SaveQrPathSelected(Option<FileHandle>),
+    /// Callback after saving the qr code, with the written path
+    QrSaved(Result<PathBuf, anywho::Error>),
 
     /// Messages of the [`ScanQrPage`]
     #[cfg(unix)]
@@ -64,6 +140,13 @@ pub enum Message {
     /// Ask to open the [`ScanQrPage`]
     #[cfg(unix)]
     OpenScanQrPage,
+
+    /// Ask the desktop portal for a screenshot of a region of the screen and decode it
+    #[cfg(unix)]
+    ScanScreen,
+    /// Callback after decoding the screenshot taken through the portal
+    #[cfg(unix)]
+    ScreenScanned(Result<Vec<String>, anywho::Error>),
 }
 
 pub enum Action {
@@ -75,12 +158,14 @@ pub enum Action {
     Run(Task<Message>),
     /// Add a new [`Toast`] to show
     AddToast(Toast),
-    /// Ask the parent to update the given [`ClockodeEntry`]
-    UpdateEntry(ClockodeEntry),
+    /// Ask the parent to update the given [`ClockodeEntry`], the second one being it as it was read
+    UpdateEntry(ClockodeEntry, ClockodeEntry),
     /// Ask the parent to create the given [`ClockodeEntry`]
     CreateEntry(ClockodeEntry),
-    /// Ask the parent to delete the [`ClockodeEntry`] with the give [`uuid::Uuid`]
-    DeleteEntry(uuid::Uuid),
+    /// Ask the parent to create all the given [`ClockodeEntry`]s at once
+    CreateEntries(Vec<ClockodeEntry>),
+    /// Ask the parent to delete the given [`ClockodeEntry`], as it was read
+    DeleteEntry(ClockodeEntry),
 }
 
 /// Represents the different inputs the user can perfrom on the upsert screen
@@ -98,12 +183,18 @@ pub enum TOTPEntryInput {
 
 impl UpsertPage {
     pub fn new(entry: Option<ClockodeEntry>) -> (Self, Task<Message>) {
+        let original = entry.clone();
         let entry = entry.map(InputableClockodeEntry::from).unwrap_or_default();
 
         (
             Self {
                 entry,
+                original,
                 show_qr: false,
+                qr_fullscreen: None,
+                qr_file_format: QrFileFormat::default(),
+                qr_png_size: 1024,
+                detected_entries: None,
                 subscreen: SubScreen::UpsertPage,
             },
             Task::none(),
@@ -114,16 +205,29 @@ impl UpsertPage {
         match &self.subscreen {
             SubScreen::UpsertPage => {
                 let header = header_view(&self.entry);
-                let content = upsert_entry_view(&self.entry, self.show_qr);
+                if let Some(handle) = &self.qr_fullscreen {
+                    return qr_fullscreen_view(handle);
+                }
 
-                container(
+                let content = upsert_entry_view(
+                    &self.entry,
+                    self.show_qr,
+                    &self.qr_file_format,
+                    &self.qr_png_size,
+                );
+
+                let page = container(
                     container(column![header, content])
                         .padding(5.)
                         .width(Length::Fill)
                         .height(Length::Fill),
                 )
-                .center(Length::Fill)
-                .into()
+                .center(Length::Fill);
+
+                match &self.detected_entries {
+                    Some(entries) => stack![page, detected_entries_view(entries)].into(),
+                    None => page.into(),
+                }
             }
             #[cfg(unix)]
             SubScreen::ScanQrPage(qr_scan_page) => qr_scan_page.view(now).map(Message::ScanQrPage),
@@ -140,7 +244,16 @@ impl UpsertPage {
                         Action::Run(focus_next())
                     }
                 }
-                Hotkey::Esc => Action::Back,
+                Hotkey::Paste => self.update(Message::PasteQrImage, now),
+                Hotkey::Esc => {
+                    if self.qr_fullscreen.take().is_some()
+                        || self.detected_entries.take().is_some()
+                    {
+                        Action::None
+                    } else {
+                        Action::Back
+                    }
+                }
             },
             Message::Back => Action::Back,
 
@@ -182,13 +295,10 @@ impl UpsertPage {
                 if self.entry.valid() {
                     let clockode_entry_res = ClockodeEntry::try_from(self.entry.clone());
                     match clockode_entry_res {
-                        Ok(clockode_entry) => {
-                            if clockode_entry.id.is_some() {
-                                Action::UpdateEntry(clockode_entry)
-                            } else {
-                                Action::CreateEntry(clockode_entry)
-                            }
-                        }
+                        Ok(clockode_entry) => match &self.original {
+                            Some(original) => Action::UpdateEntry(clockode_entry, original.clone()),
+                            None => Action::CreateEntry(clockode_entry),
+                        },
                         Err(err) => Action::AddToast(Toast::error_toast(err)),
                     }
                 } else {
@@ -196,8 +306,8 @@ impl UpsertPage {
                 }
             }
             Message::Delete => {
-                if let Some(id) = self.entry.uuid {
-                    Action::DeleteEntry(id)
+                if let Some(original) = &self.original {
+                    Action::DeleteEntry(original.clone())
                 } else {
                     Action::None
                 }
@@ -208,7 +318,7 @@ impl UpsertPage {
                     Action::Run(Task::perform(
                         async move {
                             AsyncFileDialog::new()
-                                .add_filter("Image Files", &["png", "jpeg", "jpg", "webp"])
+                                .add_filter("Image Files", QR_IMAGE_EXTENSIONS)
                                 .set_directory(dirs::download_dir().unwrap_or("/".into()))
                                 .pick_file()
                                 .await
@@ -221,21 +331,65 @@ impl UpsertPage {
             }
             Message::QrFileSelected(handle) => {
                 if let Some(file_handle) = handle {
-                    let result = read_qr_from_file(file_handle.path().to_path_buf());
-                    return match result {
-                        Ok(value) => {
-                            let conv_result = InputableClockodeEntry::try_from(value);
-                            match conv_result {
-                                Ok(entry) => {
-                                    self.entry = entry;
-                                    Action::None
-                                }
-                                Err(e) => Action::AddToast(Toast::error_toast(e)),
-                            }
-                        }
-                        Err(e) => Action::AddToast(Toast::error_toast(e)),
-                    };
+                    return self.decode_qr_file(file_handle.path().to_path_buf());
+                }
+                Action::None
+            }
+            Message::QrFileDropped(path) => {
+                if self.entry.uuid.is_some() || self.detected_entries.is_some() {
+                    return Action::None;
+                }
+
+                if is_qr_image(&path) {
+                    self.decode_qr_file(path)
+                } else {
+                    Action::AddToast(Toast::error_toast(format!(
+                        "Unsupported file, drop a {} image",
+                        QR_IMAGE_EXTENSIONS.join(", ")
+                    )))
+                }
+            }
+            Message::UrisReceived(uris) => self.handle_decoded_qr_codes(uris),
+            Message::PasteQrImage => {
+                if self.entry.uuid.is_none() && self.detected_entries.is_none() {
+                    Action::Run(Task::perform(
+                        async move { smol::unblock(read_qr_from_clipboard).await },
+                        Message::ClipboardQrDecoded,
+                    ))
+                } else {
+                    Action::None
+                }
+            }
+            Message::QrFileDecoded(result) | Message::ClipboardQrDecoded(result) => match result {
+                Ok(values) => self.handle_decoded_qr_codes(values),
+                Err(e) => Action::AddToast(Toast::error_toast(e)),
+            },
+            Message::ChooseDetectedEntry(index) => {
+                if let Some(entry) = self
+                    .detected_entries
+                    .take()
+                    .and_then(|mut entries| (index < entries.len()).then(|| entries.remove(index)))
+                {
+                    self.entry = entry;
+                }
+                Action::None
+            }
+            Message::ImportAllDetectedEntries => {
+                let Some(entries) = self.detected_entries.take() else {
+                    return Action::None;
+                };
+
+                let result = entries
+                    .into_iter()
+                    .map(ClockodeEntry::try_from)
+                    .collect::<Result<Vec<_>, _>>();
+                match result {
+                    Ok(entries) => Action::CreateEntries(entries),
+                    Err(err) => Action::AddToast(Toast::error_toast(err)),
                 }
+            }
+            Message::CloseDetectedEntries => {
+                self.detected_entries = None;
                 Action::None
             }
 
@@ -245,6 +399,85 @@ impl UpsertPage {
                 }
                 Action::None
             }
+            Message::ToggleQrFullscreen => {
+                if self.qr_fullscreen.take().is_some() {
+                    return Action::None;
+                }
+
+                match self.entry.get_qr_png(QR_FULLSCREEN_SIZE) {
+                    Ok(bytes) => {
+                        self.qr_fullscreen = Some(image::Handle::from_bytes(bytes));
+                        Action::None
+                    }
+                    Err(e) => Action::AddToast(Toast::error_toast(e)),
+                }
+            }
+            Message::ChangedQrFileFormat(format) => {
+                self.qr_file_format = format;
+                Action::None
+            }
+            Message::ChangedQrPngSize(size) => {
+                self.qr_png_size = size;
+                Action::None
+            }
+            Message::OpenSaveQrDialog => {
+                let format = self.qr_file_format;
+                let file_name = format!(
+                    "{}.{}",
+                    if self.entry.name.trim().is_empty() {
+                        "qr-code"
+                    } else {
+                        self.entry.name.trim()
+                    },
+                    format.extension()
+                );
+
+                Action::Run(Task::perform(
+                    async move {
+                        AsyncFileDialog::new()
+                            .add_filter(format.to_string(), &[format.extension()])
+                            .set_directory(dirs::download_dir().unwrap_or("/".into()))
+                            .set_file_name(file_name)
+                            .save_file()
+                            .await
+                    },
+                    Message::SaveQrPathSelected,
+                ))
+            }
+            Message::SaveQrPathSelected(handle) => {
+                let Some(file_handle) = handle else {
+                    return Action::None;
+                };
+
+                let path = file_handle.path().to_path_buf();
+                let contents = match self.qr_file_format {
+                    QrFileFormat::Png => self.entry.get_qr_png(self.qr_png_size),
+                    QrFileFormat::Svg => self.entry.get_qr_svg().map(String::into_bytes),
+                };
+
+                match contents {
+                    Ok(contents) => Action::Run(Task::perform(
+                        async move {
+                            smol::unblock(move || {
+                                write_private_file(&path, &contents).map(|_| path)
+                            })
+                            .await
+                        },
+                        Message::QrSaved,
+                    )),
+                    Err(e) => Action::AddToast(Toast::error_toast(e)),
+                }
+            }
+            Message::QrSaved(result) => match result {
+                Ok(path) => Action::AddToast(Toast::warning_toast(format!(
+                    "Saved the QR code to {}. Anyone with this image can generate your codes, keep it safe.",
+                    path.display()
+                ))),
+                Err(err) => {
+                    error!("{err}");
+                    Action::AddToast(Toast::error_toast(err))
+                }
+            },
 
             #[cfg(unix)]
             Message::ScanQrPage(message) => {
@@ -258,6 +491,7 @@ impl UpsertPage {
                         self.subscreen = SubScreen::UpsertPage;
                         Action::None
                     }
+                    scan_qr::Action::Run(task) => Action::Run(task.map(Message::ScanQrPage)),
                     scan_qr::Action::AddToast(toast) => Action::AddToast(toast),
                     scan_qr::Action::AddToastAndBack(toast) => {
                         self.subscreen = SubScreen::UpsertPage;
@@ -271,6 +505,20 @@ impl UpsertPage {
                             &self.entry.account_name
                         )))
                     }
+                    scan_qr::Action::EntriesDetected(entries) => {
+                        let result = entries
+                            .into_iter()
+                            .map(ClockodeEntry::try_from)
+                            .collect::<Result<Vec<_>, _>>();
+                        match result {
+                            Ok(entries) => {
+                                self.subscreen = SubScreen::UpsertPage;
+                                Action::CreateEntries(entries)
+                            }
+                            // stay on the scanner so the pending entries aren't lost
+                            Err(err) => Action::AddToast(Toast::error_toast(err)),
+                        }
+                    }
                 }
             }
             #[cfg(unix)]
@@ -284,6 +532,59 @@ impl UpsertPage {
                     Action::AddToast(Toast::error_toast(format!("Failed to open camera: {}", e)))
                 }
             },
+
+            #[cfg(unix)]
+            Message::ScanScreen => {
+                if self.entry.uuid.is_none() {
+                    Action::Run(Task::perform(scan_screen(), Message::ScreenScanned))
+                } else {
+                    Action::None
+                }
+            }
+            #[cfg(unix)]
+            Message::ScreenScanned(result) => match result {
+                Ok(values) => self.handle_decoded_qr_codes(values),
+                Err(e) => {
+                    error!("{e}");
+                    Action::AddToast(Toast::error_toast(e))
+                }
+            },
+        }
+    }
+
+    /// Reads the QR codes of the image at `path` in the background, answering with
+    /// [`Message::QrFileDecoded`]
+    fn decode_qr_file(&self, path: PathBuf) -> Action {
+        Action::Run(Task::perform(
+            async move { smol::unblock(move || read_qr_from_file(path)).await },
+            Message::QrFileDecoded,
+        ))
+    }
+
+    /// Fills the form with the decoded QR payloads, if there's more than one valid entry
+    /// the user is asked to pick one or import them all
+    fn handle_decoded_qr_codes(&mut self, values: Vec<String>) -> Action {
+        let mut entries = Vec::new();
+        let mut last_error = None;
+        for value in values {
+            match InputableClockodeEntry::try_from(value) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        match entries.len() {
+            0 => Action::AddToast(Toast::error_toast(
+                last_error.map_or_else(|| String::from("No QR code found"), |e| e.to_string()),
+            )),
+            1 => {
+                self.entry = entries.remove(0);
+                Action::None
+            }
+            _ => {
+                self.detected_entries = Some(entries);
+                Action::None
+            }
         }
     }
 
@@ -298,6 +599,37 @@ impl UpsertPage {
     }
 }
 
+/// Takes a screenshot through the XDG Screenshot portal, letting the user select the region
+/// holding the code, and decodes the QR codes in it. The screenshot file is removed afterwards
+#[cfg(unix)]
+async fn scan_screen() -> Result<Vec<String>, anywho::Error> {
+    use ashpd::desktop::screenshot::Screenshot;
+    use tracing::warn;
+
+    let screenshot = Screenshot::request()
+        .interactive(true)
+        .modal(true)
+        .send()
+        .await?
+        .response()?;
+
+    let path = screenshot
+        .uri()
+        .to_file_path()
+        .map_err(|_| anywho::anywho!("The screenshot is not a local file"))?;
+
+    smol::unblock(move || {
+        let result = read_qr_from_file(path.clone());
+        // the portal saves the screenshot (usually to Pictures), it may hold secrets so it's
+        // removed whether a code was found in it or not
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("Failed to remove the screenshot {:?}: {}", path, e);
+        }
+        result
+    })
+    .await
+}
+
 /// View of the header of this screen
 fn header_view<'a>(entry: &'a InputableClockodeEntry) -> Element<'a, Message> {
     iced::widget::responsive(move |size| {
@@ -342,6 +674,28 @@ fn header_view<'a>(entry: &'a InputableClockodeEntry) -> Element<'a, Message> {
                 .into(),
             );
 
+            buttons.push(
+                button(
+                    row![
+                        icons::get_icon("qr-symbolic", 21).style(|theme, _status| {
+                            let primary_style =
+                                button::primary(theme, iced::widget::button::Status::Active);
+                            iced::widget::svg::Style {
+                                color: Some(primary_style.text_color),
+                            }
+                        }),
+                        text("Paste").size(style::font_size::BODY)
+                    ]
+                    .spacing(style::spacing::TINY)
+                    .align_y(iced::Alignment::Center),
+                )
+                .width(buttons_width)
+                .style(style::primary_button)
+                .padding(8)
+                .on_press(Message::PasteQrImage)
+                .into(),
+            );
+
             #[cfg(unix)]
             buttons.push(
                 button(
@@ -364,6 +718,29 @@ fn header_view<'a>(entry: &'a InputableClockodeEntry) -> Element<'a, Message> {
                 .on_press(Message::OpenScanQrPage)
                 .into(),
             );
+
+            #[cfg(unix)]
+            buttons.push(
+                button(
+                    row![
+                        icons::get_icon("qr-symbolic", 21).style(|theme, _status| {
+                            let primary_style =
+                                button::primary(theme, iced::widget::button::Status::Active);
+                            iced::widget::svg::Style {
+                                color: Some(primary_style.text_color),
+                            }
+                        }),
+                        text("Screen").size(style::font_size::BODY)
+                    ]
+                    .spacing(style::spacing::TINY)
+                    .align_y(iced::Alignment::Center),
+                )
+                .width(buttons_width)
+                .style(style::primary_button)
+                .padding(8)
+                .on_press(Message::ScanScreen)
+                .into(),
+            );
         } else {
             // Only for existing entries
             buttons.extend([
@@ -454,6 +831,8 @@ fn header_view<'a>(entry: &'a InputableClockodeEntry) -> Element<'a, Message> {
 fn upsert_entry_view<'a>(
     entry: &'a InputableClockodeEntry,
     show_qr_code: bool,
+    qr_file_format: &'a QrFileFormat,
+    qr_png_size: &'a u32,
 ) -> Element<'a, Message> {
     let button_text = if entry.uuid.is_some() {
         "Update Entry"
@@ -613,6 +992,7 @@ fn upsert_entry_view<'a>(
                 .style(style::secondary_button),
                 // QR code image or error message
                 qr_content,
+                qr_actions_view(qr_file_format, qr_png_size),
             ]
             .spacing(style::spacing::MEDIUM)
             .padding(24),
@@ -626,6 +1006,125 @@ fn upsert_entry_view<'a>(
     }
 }
 
+/// Save and full window buttons of the QR code modal
+fn qr_actions_view<'a>(
+    qr_file_format: &'a QrFileFormat,
+    qr_png_size: &'a u32,
+) -> Element<'a, Message> {
+    let size_picker: Option<Element<Message>> = (*qr_file_format == QrFileFormat::Png).then(|| {
+        pick_list(Some(qr_png_size), QR_PNG_SIZES, |v: &u32| format!("{v}px"))
+            .on_select(Message::ChangedQrPngSize)
+            .padding(8)
+            .into()
+    });
+
+    row![
+        pick_list(Some(qr_file_format), QrFileFormat::ALL, |v: &_| v.to_string())
+            .on_select(Message::ChangedQrFileFormat)
+            .padding(8),
+        size_picker,
+        button(text("Save QR as…").size(style::font_size::BODY))
+            .on_press(Message::OpenSaveQrDialog)
+            .padding(8)
+            .style(style::primary_button),
+        space().width(Length::Fill),
+        button(text("Full Window").size(style::font_size::BODY))
+            .on_press(Message::ToggleQrFullscreen)
+            .padding(8)
+            .style(style::secondary_button),
+    ]
+    .spacing(style::spacing::SMALL)
+    .align_y(Alignment::Center)
+    .width(Length::Fixed(400.0))
+    .into()
+}
+
+/// Black on white QR code filling the whole window, easier to scan from a distance
+fn qr_fullscreen_view(handle: &image::Handle) -> Element<'_, Message> {
+    let content = column![
+        image(handle.clone())
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .content_fit(iced::ContentFit::Contain)
+            .filter_method(image::FilterMethod::Nearest),
+        text("Press Esc or click to close")
+            .size(style::font_size::BODY)
+            .color(iced::Color::BLACK),
+    ]
+    .spacing(style::spacing::SMALL)
+    .align_x(Alignment::Center);
+
+    iced::widget::mouse_area(
+        container(content)
+            .padding(20)
+            .center(Length::Fill)
+            .style(style::qr_fullscreen_container),
+    )
+    .on_press(Message::ToggleQrFullscreen)
+    .into()
+}
+
+/// Modal listing the entries found in an image with more than one QR code
+fn detected_entries_view<'a>(entries: &'a [InputableClockodeEntry]) -> Element<'a, Message> {
+    let entries_list = entries.iter().enumerate().fold(
+        column![].spacing(style::spacing::SMALL),
+        |col, (index, entry)| {
+            col.push(
+                container(
+                    row![
+                        column![
+                            text(&entry.name)
+                                .wrapping(text::Wrapping::Glyph)
+                                .size(style::font_size::MEDIUM),
+                            text(entry.issuer.as_deref().unwrap_or(&entry.account_name))
+                                .size(style::font_size::SMALL)
+                                .style(style::muted_text),
+                        ]
+                        .spacing(style::spacing::TINY)
+                        .width(Length::Fill),
+                        button(text("Use").size(style::font_size::BODY))
+                            .on_press(Message::ChooseDetectedEntry(index))
+                            .padding(8)
+                            .style(style::primary_button),
+                    ]
+                    .spacing(style::spacing::SMALL)
+                    .align_y(Alignment::Center),
+                )
+                .padding(12)
+                .style(style::entry_card),
+            )
+        },
+    );
+
+    let dialog = container(
+        column![
+            text(format!("{} QR Codes Found", entries.len())).size(style::font_size::TITLE),
+            text("Pick the entry to fill the form with, or import all of them at once")
+                .size(style::font_size::BODY)
+                .style(style::muted_text),
+            scrollable(entries_list).height(Length::Fill.max(400)),
+            row![
+                button(text("Cancel").size(style::font_size::BODY))
+                    .on_press(Message::CloseDetectedEntries)
+                    .padding(8)
+                    .style(style::secondary_button),
+                space().width(Length::Fill),
+                button(text("Import All").size(style::font_size::BODY))
+                    .on_press(Message::ImportAllDetectedEntries)
+                    .padding(8)
+                    .style(style::primary_button),
+            ]
+            .align_y(Alignment::Center),
+        ]
+        .spacing(style::spacing::MEDIUM)
+        .padding(24)
+        .width(Length::Fill.max(500)),
+    )
+    .style(style::card_container);
+
+    container(dialog).padding(20).center(Length::Fill).into()
+}
+
 //
 // SUBSCRIPTIONS
 //
@@ -634,16 +1133,26 @@ fn upsert_entry_view<'a>(
 pub enum Hotkey {
     Tab(Modifiers),
     Esc,
+    Paste,
 }
 
-fn handle_event(event: event::Event, _: event::Status, _: iced::window::Id) -> Option<Message> {
+fn handle_event(event: event::Event, status: event::Status, _: iced::window::Id) -> Option<Message> {
     #[allow(clippy::collapsible_match)]
     match event {
         event::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => match key {
             Key::Named(Named::Tab) => Some(Message::Hotkey(Hotkey::Tab(modifiers))),
             Key::Named(Named::Escape) => Some(Message::Hotkey(Hotkey::Esc)),
+            // a focused text input captures the shortcut to paste text instead
+            Key::Character(c)
+                if c.as_str() == "v" && modifiers.command() && status == event::Status::Ignored =>
+            {
+                Some(Message::Hotkey(Hotkey::Paste))
+            }
             _ => None,
         },
+        event::Event::Window(iced::window::Event::FileDropped(path)) => {
+            Some(Message::QrFileDropped(path))
+        }
         _ => None,
     }
 }
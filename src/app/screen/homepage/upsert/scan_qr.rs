@@ -1,9 +1,13 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use ::image::GrayImage;
 use anywho::anywho;
 use gstreamer::{
     self as gst,
-    glib::{self, object::Cast},
+    glib::{
+        self,
+        object::{Cast, ObjectExt},
+    },
     prelude::{ElementExt, GstBinExtManual},
 };
 use gstreamer_app as gst_app;
@@ -13,26 +17,51 @@ use iced::{
     Subscription, Task, event,
     keyboard::{self, Key, key::Named},
     time::Instant,
-    widget::{button, column, container, image, stack, text},
+    widget::{
+        button, checkbox, column, container, image, pick_list, row, scrollable, space, stack, text,
+    },
 };
+use rfd::{AsyncFileDialog, FileHandle};
 use smol::channel;
 use std::{
+    collections::HashSet,
     error::Error,
     fmt,
     os::fd::{AsRawFd, OwnedFd},
+    path::PathBuf,
     sync::Arc,
 };
+use tracing::warn;
+
+use clockode_core::{InputableClockodeEntry, decode_qr_codes};
 
 use crate::{
-    app::{
-        utils::{InputableClockodeEntry, style},
-        widgets::Toast,
-    },
+    app::{utils::style, widgets::Toast},
     icons,
 };
 
 pub struct QrScanPage {
     state: State,
+    /// Video sources the user can pick from, the portal camera always comes first
+    sources: Vec<CameraSource>,
+    source: CameraSource,
+    resolution: Resolution,
+    /// Set once the camera portal granted access, reused when switching back to the portal camera
+    camera_fd: Option<Arc<OwnedFd>>,
+    /// Bumped on every pipeline (re)build so the frame subscriptions restart with the new channels
+    generation: usize,
+    /// Keep scanning after a detection, collecting every new code instead of going back to the form
+    batch_mode: bool,
+    /// Entries collected while in batch mode, waiting to be saved together
+    pending: Vec<PendingEntry>,
+    /// Raw payloads already handled, so the same code seen on consecutive frames is ignored
+    seen: HashSet<String>,
+}
+
+/// An entry collected in batch mode, with the camera frame it was detected on
+struct PendingEntry {
+    entry: InputableClockodeEntry,
+    preview: Option<image::Handle>,
 }
 
 enum State {
@@ -43,20 +72,107 @@ enum State {
 pub struct PermittedState {
     display_frame: Option<Box<image::Handle>>,
     pipeline: gst::Pipeline,
+    /// Closed once a code is accepted, so frames stop reaching the decoder
+    decode_valve: gst::Element,
     frame_rx: channel::Receiver<FrameData>,
     display_rx: channel::Receiver<image::Handle>,
+    generation: usize,
     _camera_fd: Option<Arc<OwnedFd>>, // we need to keep fd alive
 }
 
-impl Drop for QrScanPage {
+impl Drop for PermittedState {
     fn drop(&mut self) {
-        if let State::Permitted(state) = &mut self.state {
-            let _ = state.pipeline.set_state(gst::State::Null);
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
+
+/// Hidden hook to run the scanner without a camera, set it to `test` for the
+/// `videotestsrc` pattern or to the path of a video file to scan
+const SOURCE_OVERRIDE_VAR: &str = "CLOCKODE_SCAN_SOURCE";
+
+/// Where the scanner pulls its video frames from
+#[derive(Debug, Clone, PartialEq)]
+pub enum CameraSource {
+    /// Default camera shared through the XDG Camera portal (PipeWire)
+    Portal,
+    /// A device found by the GStreamer device monitor (V4L2, PipeWire...)
+    Device(gst::Device),
+    /// Let GStreamer pick a source with `autovideosrc`
+    Auto,
+    /// A recorded video, decoded with `decodebin`
+    File(PathBuf),
+    /// The `videotestsrc` pattern, to try the scanner without any camera
+    TestPattern,
+}
+
+impl CameraSource {
+    /// Source forced through [`SOURCE_OVERRIDE_VAR`], if set
+    fn from_env() -> Option<Self> {
+        let value = std::env::var_os(SOURCE_OVERRIDE_VAR)?;
+        if value == "test" {
+            Some(CameraSource::TestPattern)
+        } else {
+            Some(CameraSource::File(PathBuf::from(value)))
+        }
+    }
+}
+
+impl fmt::Display for CameraSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use gst::prelude::DeviceExt;
+
+        match self {
+            CameraSource::Portal => write!(f, "Default camera (portal)"),
+            CameraSource::Device(device) => write!(f, "{}", device.display_name()),
+            CameraSource::Auto => write!(f, "Automatic"),
+            CameraSource::File(path) => match path.file_name() {
+                Some(name) => write!(f, "{}", name.to_string_lossy()),
+                None => write!(f, "{}", path.display()),
+            },
+            CameraSource::TestPattern => write!(f, "Test pattern"),
         }
     }
 }
 
-#[derive(Clone)]
+/// Maximum frames per second handed to the QR decoder
+const DECODE_FPS: i32 = 4;
+
+/// Size of the frames the pipeline outputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+impl Resolution {
+    pub const ALL: &'static [Self] = &[Self::Low, Self::Medium, Self::High];
+
+    fn dimensions(&self) -> (i32, i32) {
+        match self {
+            Resolution::Low => (640, 480),
+            Resolution::Medium => (1280, 720),
+            Resolution::High => (1920, 1080),
+        }
+    }
+
+    /// Size of the frames handed to the decoder, smaller than the displayed ones on higher resolutions
+    fn decode_dimensions(&self) -> (i32, i32) {
+        match self {
+            Resolution::Low => (640, 480),
+            Resolution::Medium | Resolution::High => (960, 540),
+        }
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, height) = self.dimensions();
+        write!(f, "{width}x{height}")
+    }
+}
+
 struct FrameData {
     width: u32,
     height: u32,
@@ -75,6 +191,22 @@ pub enum Message {
     QrDetected(String),
     /// Updates the frame to be displayed
     UpdateDisplayFrame(Box<image::Handle>),
+    /// Switch to another video source
+    SourceSelected(CameraSource),
+    /// Change the resolution frames are captured at
+    ResolutionSelected(Resolution),
+    /// Open the dialog to select a video file to scan
+    OpenVideoFileSelection,
+    /// Callback after selecting a video file
+    VideoFileSelected(Option<FileHandle>),
+    /// Switch between going back on the first detection and collecting every detected code
+    ToggleBatchMode(bool),
+    /// Remove the pending entry at the given index
+    RemovePending(usize),
+    /// Drop every pending entry
+    DiscardPending,
+    /// Save every pending entry at once
+    FinishBatch,
 }
 
 pub enum Action {
@@ -82,12 +214,16 @@ pub enum Action {
     None,
     /// Go back a screen
     Back,
+    // Ask parent to run an [`iced::Task`]
+    Run(Task<Message>),
     /// Add a new [`Toast`] to show
     AddToast(Toast),
     /// Add a new [`Toast`] to show and goes back
     AddToastAndBack(Toast),
     /// Callback after an entry has been detected
     EntryDetected(InputableClockodeEntry),
+    /// Callback after finishing a batch scan with every collected entry
+    EntriesDetected(Vec<InputableClockodeEntry>),
 }
 
 #[derive(Debug)]
@@ -96,6 +232,7 @@ pub enum QrScanError {
     ElementCreation(&'static str),
     PipelineSetup(glib::BoolError),
     StateChange(gst::StateChangeError),
+    NoCameraAccess,
 }
 
 impl fmt::Display for QrScanError {
@@ -105,6 +242,7 @@ impl fmt::Display for QrScanError {
             QrScanError::ElementCreation(name) => write!(f, "Failed to create element: {}", name),
             QrScanError::PipelineSetup(e) => write!(f, "Failed to setup pipeline: {}", e),
             QrScanError::StateChange(e) => write!(f, "Failed to start pipeline: {}", e),
+            QrScanError::NoCameraAccess => write!(f, "Camera access was not granted"),
         }
     }
 }
@@ -115,24 +253,43 @@ impl QrScanPage {
     pub fn new() -> Result<(Self, Task<Message>), QrScanError> {
         gst::init().map_err(QrScanError::GStreamerInit)?;
 
-        Ok((
-            Self {
-                state: State::AskingPermission,
+        let mut page = Self {
+            state: State::AskingPermission,
+            sources: list_sources(),
+            source: CameraSource::Portal,
+            resolution: Resolution::default(),
+            camera_fd: None,
+            generation: 0,
+            batch_mode: false,
+            pending: Vec::new(),
+            seen: HashSet::new(),
+        };
+
+        if let Some(source) = CameraSource::from_env() {
+            warn!("Scanning from {source} ({SOURCE_OVERRIDE_VAR} is set)");
+            page.sources.push(source.clone());
+            page.source = source;
+            page.start_pipeline()?;
+            return Ok((page, Task::none()));
+        }
+
+        Ok((page, Self::ask_permission()))
+    }
+
+    fn ask_permission() -> Task<Message> {
+        Task::perform(
+            async {
+                smol::future::or(
+                    async { Self::request_camera_access().await.map(Arc::new) },
+                    async {
+                        smol::Timer::after(std::time::Duration::from_secs(30)).await;
+                        Err(anywho!("Permission request timed out"))
+                    },
+                )
+                .await
             },
-            Task::perform(
-                async {
-                    smol::future::or(
-                        async { Self::request_camera_access().await.map(Arc::new) },
-                        async {
-                            smol::Timer::after(std::time::Duration::from_secs(30)).await;
-                            Err(anywho!("Permission request timed out"))
-                        },
-                    )
-                    .await
-                },
-                Message::PermissionCallback,
-            ),
-        ))
+            Message::PermissionCallback,
+        )
     }
 
     pub fn view(&self, _now: Instant) -> iced::Element<'_, Message> {
@@ -140,40 +297,186 @@ impl QrScanPage {
             State::AskingPermission => container(text("Asking for camera permission..."))
                 .center(Length::Fill)
                 .into(),
-            State::Permitted(state) => qr_scan_view(&state.display_frame),
+            State::Permitted(state) => {
+                let scan = qr_scan_view(
+                    &state.display_frame,
+                    self.batch_mode,
+                    &self.sources,
+                    &self.source,
+                    self.resolution,
+                );
+                if self.batch_mode {
+                    row![scan, pending_entries_view(&self.pending)]
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .into()
+                } else {
+                    scan
+                }
+            }
         };
 
         container(content).padding(5.).center(Length::Fill).into()
     }
 
-    pub fn update(&mut self, message: Message, _now: Instant) -> Action {
+    pub fn update(&mut self, message: Message, now: Instant) -> Action {
         match message {
-            Message::Back => Action::Back,
+            Message::Back => self.back(),
             Message::Hotkey(hotkey) => match hotkey {
-                Hotkey::Esc => Action::Back,
+                Hotkey::Esc => self.back(),
             },
             Message::PermissionCallback(res) => match res {
-                Ok(fd) => match Self::init_gstreamer(fd) {
-                    Ok(state) => {
-                        self.state = state;
-                        Action::None
+                Ok(fd) => {
+                    self.camera_fd = Some(fd);
+                    match self.start_pipeline() {
+                        Ok(()) => Action::None,
+                        Err(err) => Action::AddToastAndBack(Toast::error_toast(err)),
                     }
-                    Err(err) => Action::AddToastAndBack(Toast::error_toast(err)),
-                },
-                Err(err) => Action::AddToastAndBack(Toast::error_toast(err)),
-            },
-            Message::QrDetected(data) => match InputableClockodeEntry::try_from(data) {
-                Ok(entry) => Action::EntryDetected(entry),
-                Err(_) => Action::AddToast(Toast::warning_toast(
-                    "QR Detected but it could not be decoded into an entry",
-                )),
+                }
+                Err(err) => {
+                    // outside of a sandbox there may be no camera portal, use the cameras directly
+                    warn!("Camera portal unavailable: {err}");
+                    let Some(fallback) = self
+                        .sources
+                        .iter()
+                        .find(|source| **source != CameraSource::Portal)
+                        .cloned()
+                    else {
+                        return Action::AddToastAndBack(Toast::error_toast(err));
+                    };
+
+                    self.source = fallback;
+                    match self.start_pipeline() {
+                        Ok(()) => Action::AddToast(Toast::warning_toast(format!(
+                            "Camera portal unavailable, using {}",
+                            self.source
+                        ))),
+                        Err(err) => Action::AddToastAndBack(Toast::error_toast(err)),
+                    }
+                }
             },
+            Message::QrDetected(data) => {
+                if self.batch_mode && !self.seen.insert(data.clone()) {
+                    // same code as an earlier frame
+                    return Action::None;
+                }
+
+                match InputableClockodeEntry::try_from(data) {
+                    Ok(entry) if self.batch_mode => {
+                        let toast = Toast::success_toast(format!(
+                            "Added {} to the pending entries",
+                            &entry.account_name
+                        ));
+                        let preview = match &self.state {
+                            State::Permitted(state) => state.display_frame.as_deref().cloned(),
+                            State::AskingPermission => None,
+                        };
+                        self.pending.push(PendingEntry { entry, preview });
+                        Action::AddToast(toast)
+                    }
+                    Ok(entry) => {
+                        if let State::Permitted(state) = &self.state {
+                            state.decode_valve.set_property("drop", true);
+                        }
+                        Action::EntryDetected(entry)
+                    }
+                    Err(_) => Action::AddToast(Toast::warning_toast(
+                        "QR Detected but it could not be decoded into an entry",
+                    )),
+                }
+            }
             Message::UpdateDisplayFrame(handle) => {
                 if let State::Permitted(state) = &mut self.state {
                     state.display_frame = Some(handle);
                 }
                 Action::None
             }
+            Message::SourceSelected(source) => {
+                self.source = source;
+                if self.source == CameraSource::Portal && self.camera_fd.is_none() {
+                    self.state = State::AskingPermission;
+                    return Action::Run(Self::ask_permission());
+                }
+
+                match self.start_pipeline() {
+                    Ok(()) => Action::None,
+                    Err(err) => Action::AddToastAndBack(Toast::error_toast(err)),
+                }
+            }
+            Message::OpenVideoFileSelection => Action::Run(Task::perform(
+                async move {
+                    AsyncFileDialog::new()
+                        .add_filter("Video Files", &["mp4", "mkv", "webm", "mov", "avi", "ogv"])
+                        .set_directory(dirs::video_dir().unwrap_or("/".into()))
+                        .pick_file()
+                        .await
+                },
+                Message::VideoFileSelected,
+            )),
+            Message::VideoFileSelected(handle) => {
+                let Some(file_handle) = handle else {
+                    return Action::None;
+                };
+
+                let source = CameraSource::File(file_handle.path().to_path_buf());
+                if !self.sources.contains(&source) {
+                    self.sources.push(source.clone());
+                }
+                self.update(Message::SourceSelected(source), now)
+            }
+            Message::ResolutionSelected(resolution) => {
+                self.resolution = resolution;
+                match self.start_pipeline() {
+                    Ok(()) => Action::None,
+                    Err(err) => Action::AddToastAndBack(Toast::error_toast(err)),
+                }
+            }
+            Message::ToggleBatchMode(enabled) => {
+                if enabled || self.pending.is_empty() {
+                    self.batch_mode = enabled;
+                    self.seen.clear();
+                    Action::None
+                } else {
+                    Action::AddToast(Toast::warning_toast(
+                        "Save or discard the pending entries first",
+                    ))
+                }
+            }
+            Message::RemovePending(index) => {
+                if index < self.pending.len() {
+                    // the code stays in `seen`, pointing the camera at it again won't re-add it
+                    self.pending.remove(index);
+                }
+                Action::None
+            }
+            Message::DiscardPending => {
+                self.pending.clear();
+                self.seen.clear();
+                Action::None
+            }
+            Message::FinishBatch => {
+                if self.pending.is_empty() {
+                    return Action::None;
+                }
+
+                Action::EntriesDetected(
+                    self.pending
+                        .iter()
+                        .map(|pending| pending.entry.clone())
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Goes back unless there are pending entries that would be lost
+    fn back(&self) -> Action {
+        if self.pending.is_empty() {
+            Action::Back
+        } else {
+            Action::AddToast(Toast::warning_toast(
+                "Save or discard the pending entries before leaving",
+            ))
         }
     }
 
@@ -181,8 +484,8 @@ impl QrScanPage {
         match &self.state {
             State::AskingPermission => Subscription::none(),
             State::Permitted(state) => Subscription::batch([
-                Self::qr_detection(state.frame_rx.clone()),
-                Self::update_camera_feed(state.display_rx.clone()),
+                Self::qr_detection(state.frame_rx.clone(), state.generation),
+                Self::update_camera_feed(state.display_rx.clone(), state.generation),
                 event::listen_with(handle_event),
             ]),
         }
@@ -199,81 +502,53 @@ impl QrScanPage {
             .await?)
     }
 
-    fn init_gstreamer(camera_fd: Arc<OwnedFd>) -> Result<State, QrScanError> {
-        let pipeline = gst::Pipeline::new();
-
-            let src = gst::ElementFactory::make("pipewiresrc")
-                .property("fd", camera_fd.as_raw_fd())
-                .property("do-timestamp", true)  // helps with mobile cameras
-                .build()
-                .map_err(|_| QrScanError::ElementCreation("pipewiresrc"))?;
+    /// (Re)builds the pipeline for the selected source and resolution and starts it
+    fn start_pipeline(&mut self) -> Result<(), QrScanError> {
+        // stop the current pipeline first, some devices can't be opened twice
+        self.state = State::AskingPermission;
 
-            let convert = gst::ElementFactory::make("videoconvert")
-                .build()
-                .map_err(|_| QrScanError::ElementCreation("videoconvert"))?;
-
-            let scale = gst::ElementFactory::make("videoscale")
-                .build()
-                .map_err(|_| QrScanError::ElementCreation("videoscale"))?;
-
-            let capsfilter = gst::ElementFactory::make("capsfilter")
-                .property(
-                    "caps",
-                    gst::Caps::builder("video/x-raw")
-                        .field("format", "GRAY8")
-                        .field("width", 640i32)
-                        .field("height", 480i32)
-                        .build(),
-                )
-                .build()
-                .map_err(|_| QrScanError::ElementCreation("capsfilter"))?;
+        let ScanPipeline {
+            pipeline,
+            display_sink,
+            decode_sink,
+            decode_valve,
+        } = build_pipeline(&self.source, self.resolution, self.camera_fd.as_deref())?;
 
-            let sink = gst::ElementFactory::make("appsink")
-                .build()
-                .map_err(|_| QrScanError::ElementCreation("appsink"))?;
+        let (frame_tx, frame_rx) = channel::bounded::<FrameData>(1);
+        let (display_tx, display_rx) = channel::bounded::<image::Handle>(1);
 
-            pipeline
-                .add_many([&src, &convert, &scale, &capsfilter, &sink])
-                .map_err(QrScanError::PipelineSetup)?;
-            gst::Element::link_many([&src, &convert, &scale, &capsfilter, &sink])
-                .map_err(QrScanError::PipelineSetup)?;
+        // the display branch is already RGBA, the buffer is copied once into the handle
+        display_sink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Error)?;
+                    let (w, h) = sample_dimensions(&sample)?;
+                    let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+                    let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
 
-            let appsink = sink
-                .dynamic_cast::<gst_app::AppSink>()
-                .map_err(|_| QrScanError::ElementCreation("appsink cast failed"))?;
+                    let _ = display_tx.try_send(image::Handle::from_rgba(w, h, map.to_vec()));
 
-        let (frame_tx, frame_rx) = channel::bounded::<FrameData>(1);
-        let (display_tx, display_rx) = channel::bounded::<image::Handle>(1);
+                    Ok(gst::FlowSuccess::Ok)
+                })
+                .build(),
+        );
 
-        appsink.set_callbacks(
+        decode_sink.set_callbacks(
             gst_app::AppSinkCallbacks::builder()
                 .new_sample(move |sink| {
                     let sample = sink.pull_sample().map_err(|_| gst::FlowError::Error)?;
+                    let (w, h) = sample_dimensions(&sample)?;
                     let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
                     let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
-                    let caps = sample.caps().ok_or(gst::FlowError::Error)?;
-                    let s = caps.structure(0).ok_or(gst::FlowError::Error)?;
-                    let (w, h) = (
-                        s.get::<i32>("width").map_err(|_| gst::FlowError::Error)? as u32,
-                        s.get::<i32>("height").map_err(|_| gst::FlowError::Error)? as u32,
-                    );
-
-                    let data = map.to_vec();
-
-                    // Update display frame
-                    let mut rgba = Vec::with_capacity((w * h * 4) as usize);
-                    for &gray in &data {
-                        rgba.extend_from_slice(&[gray, gray, gray, 255]);
-                    }
-                    let handle = image::Handle::from_rgba(w, h, rgba);
-                    let _ = display_tx.try_send(handle);
 
-                    // Send grayscale data to QR decoder
-                    let _ = frame_tx.try_send(FrameData {
-                        width: w,
-                        height: h,
-                        data,
-                    });
+                    // skip the copy entirely if the decoder is still busy with the last frame
+                    if !frame_tx.is_full() {
+                        let _ = frame_tx.try_send(FrameData {
+                            width: w,
+                            height: h,
+                            data: map.to_vec(),
+                        });
+                    }
 
                     Ok(gst::FlowSuccess::Ok)
                 })
@@ -284,20 +559,27 @@ impl QrScanPage {
             .set_state(gst::State::Playing)
             .map_err(QrScanError::StateChange)?;
 
-        Ok(State::Permitted(Box::new(PermittedState {
+        self.generation += 1;
+        self.state = State::Permitted(Box::new(PermittedState {
             display_frame: None,
             pipeline,
+            decode_valve,
             frame_rx,
             display_rx,
-            _camera_fd: Some(camera_fd),
-        })))
+            generation: self.generation,
+            _camera_fd: self.camera_fd.clone(),
+        }));
+        Ok(())
     }
 
-    fn update_camera_feed(display_rx: channel::Receiver<image::Handle>) -> Subscription<Message> {
+    fn update_camera_feed(
+        display_rx: channel::Receiver<image::Handle>,
+        generation: usize,
+    ) -> Subscription<Message> {
         use iced::futures::sink::SinkExt;
 
         #[derive(Hash, Clone, Copy)]
-        struct DisplayUpdaterId;
+        struct DisplayUpdaterId(usize);
 
         #[derive(Clone)]
         struct DisplayData {
@@ -314,7 +596,7 @@ impl QrScanPage {
         }
 
         let data = DisplayData {
-            id: DisplayUpdaterId,
+            id: DisplayUpdaterId(generation),
             display_rx,
         };
 
@@ -339,11 +621,14 @@ impl QrScanPage {
 
 /// QR Helper Functions
 impl QrScanPage {
-    fn qr_detection(frame_rx: channel::Receiver<FrameData>) -> Subscription<Message> {
+    fn qr_detection(
+        frame_rx: channel::Receiver<FrameData>,
+        generation: usize,
+    ) -> Subscription<Message> {
         use iced::futures::sink::SinkExt;
 
         #[derive(Hash, Clone, Copy)]
-        struct QrScannerId;
+        struct QrScannerId(usize);
 
         #[derive(Clone)]
         struct ScannerData {
@@ -360,7 +645,7 @@ impl QrScanPage {
         }
 
         let data = ScannerData {
-            id: QrScannerId,
+            id: QrScannerId(generation),
             frame_rx,
         };
 
@@ -370,19 +655,13 @@ impl QrScanPage {
             iced::stream::channel(
                 10,
                 move |mut output: iced::futures::channel::mpsc::Sender<Message>| async move {
-                    let mut last_check = std::time::Instant::now();
-                    let interval = std::time::Duration::from_millis(300);
-
+                    // frames already come rate limited and downscaled from the pipeline,
+                    // and the channel drops them while a previous one is still being decoded
                     while let Ok(frame) = frame_rx.recv().await {
-                        if last_check.elapsed() >= interval {
-                            let frame_to_decode = frame.clone();
-
-                            let res = smol::unblock(move || Self::decode_qr(frame_to_decode)).await;
+                        let res = smol::unblock(move || Self::decode_qr(frame)).await;
 
-                            if let Some(content) = res {
-                                let _ = output.send(Message::QrDetected(content)).await;
-                                last_check = std::time::Instant::now();
-                            }
+                        if let Some(content) = res {
+                            let _ = output.send(Message::QrDetected(content)).await;
                         }
                     }
 
@@ -392,21 +671,276 @@ impl QrScanPage {
         })
     }
 
+    /// Decodes a frame with the same preprocessing as QR images, frames come rate limited
+    /// and downscaled so the whole pipeline keeps up
     fn decode_qr(frame: FrameData) -> Option<String> {
-        let mut img = rqrr::PreparedImage::prepare_from_greyscale(
-            frame.width as usize,
-            frame.height as usize,
-            |x, y| frame.data[y * frame.width as usize + x],
-        );
+        let img = GrayImage::from_raw(frame.width, frame.height, frame.data)?;
+        decode_qr_codes(&img).into_iter().next()
+    }
+}
+
+/// Lists the video sources to pick from: the portal camera, every video device
+/// GStreamer can see and `autovideosrc` as a last resort
+fn list_sources() -> Vec<CameraSource> {
+    use gst::prelude::*;
 
-        img.detect_grids()
-            .first()
-            .and_then(|grid| grid.decode().ok())
-            .map(|(_, content)| content)
+    let mut sources = vec![CameraSource::Portal];
+
+    let monitor = gst::DeviceMonitor::new();
+    monitor.add_filter(Some("Video/Source"), None);
+    match monitor.start() {
+        Ok(()) => {
+            sources.extend(monitor.devices().into_iter().map(CameraSource::Device));
+            monitor.stop();
+        }
+        Err(e) => warn!("Could not list the video devices: {e}"),
     }
+
+    sources.push(CameraSource::Auto);
+    sources
 }
 
-fn qr_scan_view<'a>(display_frame: &'a Option<Box<image::Handle>>) -> Element<'a, Message> {
+/// Elements of a built pipeline the page keeps a handle on
+struct ScanPipeline {
+    pipeline: gst::Pipeline,
+    /// Full size RGBA frames to show on screen
+    display_sink: gst_app::AppSink,
+    /// Rate limited, downscaled GRAY8 frames for the QR decoder
+    decode_sink: gst_app::AppSink,
+    /// Lets frames through to `decode_sink` until its `drop` property is set
+    decode_valve: gst::Element,
+}
+
+/// Builds the scanning pipeline, it's not started and the appsinks have no callbacks yet:
+///
+/// `source ! videoconvert ! videoscale ! caps ! tee` splits into
+/// `queue ! videoconvert ! RGBA caps ! appsink` for the display and
+/// `queue ! valve ! videorate ! videoscale ! videoconvert ! GRAY8 caps ! appsink` for the decoder.
+fn build_pipeline(
+    source: &CameraSource,
+    resolution: Resolution,
+    camera_fd: Option<&OwnedFd>,
+) -> Result<ScanPipeline, QrScanError> {
+    use gst::prelude::*;
+
+    fn make(factory: &'static str) -> Result<gst::Element, QrScanError> {
+        gst::ElementFactory::make(factory)
+            .build()
+            .map_err(|_| QrScanError::ElementCreation(factory))
+    }
+
+    fn caps_filter(caps: gst::Caps) -> Result<gst::Element, QrScanError> {
+        gst::ElementFactory::make("capsfilter")
+            .property("caps", caps)
+            .build()
+            .map_err(|_| QrScanError::ElementCreation("capsfilter"))
+    }
+
+    /// Queue that only ever holds the latest frame, so a slow branch never stalls the other one
+    fn leaky_queue() -> Result<gst::Element, QrScanError> {
+        gst::ElementFactory::make("queue")
+            .property("max-size-buffers", 1u32)
+            .property("max-size-bytes", 0u32)
+            .property("max-size-time", 0u64)
+            .property_from_str("leaky", "downstream")
+            .build()
+            .map_err(|_| QrScanError::ElementCreation("queue"))
+    }
+
+    fn app_sink() -> Result<gst_app::AppSink, QrScanError> {
+        gst::ElementFactory::make("appsink")
+            .property("max-buffers", 1u32)
+            .property("drop", true)
+            .build()
+            .map_err(|_| QrScanError::ElementCreation("appsink"))?
+            .dynamic_cast::<gst_app::AppSink>()
+            .map_err(|_| QrScanError::ElementCreation("appsink cast failed"))
+    }
+
+    let pipeline = gst::Pipeline::new();
+
+    // shared head
+    let convert = make("videoconvert")?;
+    let scale = make("videoscale")?;
+    let (width, height) = resolution.dimensions();
+    let capsfilter = caps_filter(
+        gst::Caps::builder("video/x-raw")
+            .field("width", width)
+            .field("height", height)
+            .build(),
+    )?;
+    let tee = make("tee")?;
+
+    // display branch
+    let display_queue = leaky_queue()?;
+    let display_convert = make("videoconvert")?;
+    let display_caps = caps_filter(
+        gst::Caps::builder("video/x-raw")
+            .field("format", "RGBA")
+            .build(),
+    )?;
+    let display_sink = app_sink()?;
+
+    // decode branch
+    let decode_queue = leaky_queue()?;
+    let decode_valve = make("valve")?;
+    let decode_rate = gst::ElementFactory::make("videorate")
+        .property("drop-only", true)
+        .property("max-rate", DECODE_FPS)
+        .build()
+        .map_err(|_| QrScanError::ElementCreation("videorate"))?;
+    let decode_scale = make("videoscale")?;
+    let decode_convert = make("videoconvert")?;
+    let (decode_width, decode_height) = resolution.decode_dimensions();
+    let decode_caps = caps_filter(
+        gst::Caps::builder("video/x-raw")
+            .field("format", "GRAY8")
+            .field("width", decode_width)
+            .field("height", decode_height)
+            .build(),
+    )?;
+    let decode_sink = app_sink()?;
+
+    pipeline
+        .add_many([
+            &convert,
+            &scale,
+            &capsfilter,
+            &tee,
+            &display_queue,
+            &display_convert,
+            &display_caps,
+            display_sink.upcast_ref(),
+            &decode_queue,
+            &decode_valve,
+            &decode_rate,
+            &decode_scale,
+            &decode_convert,
+            &decode_caps,
+            decode_sink.upcast_ref(),
+        ])
+        .map_err(QrScanError::PipelineSetup)?;
+    gst::Element::link_many([&convert, &scale, &capsfilter, &tee])
+        .map_err(QrScanError::PipelineSetup)?;
+    gst::Element::link_many([
+        &tee,
+        &display_queue,
+        &display_convert,
+        &display_caps,
+        display_sink.upcast_ref(),
+    ])
+    .map_err(QrScanError::PipelineSetup)?;
+    gst::Element::link_many([
+        &tee,
+        &decode_queue,
+        &decode_valve,
+        &decode_rate,
+        &decode_scale,
+        &decode_convert,
+        &decode_caps,
+        decode_sink.upcast_ref(),
+    ])
+    .map_err(QrScanError::PipelineSetup)?;
+
+    // video files can't be linked to the rest of the pipeline until decodebin finds the stream
+    let static_src = match source {
+        CameraSource::File(path) => {
+            let filesrc = gst::ElementFactory::make("filesrc")
+                .property("location", path.to_string_lossy().as_ref())
+                .build()
+                .map_err(|_| QrScanError::ElementCreation("filesrc"))?;
+
+            let decodebin = make("decodebin")?;
+
+            pipeline
+                .add_many([&filesrc, &decodebin])
+                .map_err(QrScanError::PipelineSetup)?;
+            filesrc
+                .link(&decodebin)
+                .map_err(QrScanError::PipelineSetup)?;
+
+            // decodebin only exposes its pads once it knows what's inside the file
+            let convert_weak = convert.downgrade();
+            decodebin.connect_pad_added(move |_, pad| {
+                let Some(convert) = convert_weak.upgrade() else {
+                    return;
+                };
+
+                let is_video = pad
+                    .current_caps()
+                    .and_then(|caps| {
+                        caps.structure(0)
+                            .map(|s| s.name().as_str().starts_with("video/"))
+                    })
+                    .unwrap_or(false);
+                let Some(sink_pad) = convert.static_pad("sink") else {
+                    return;
+                };
+                if !is_video || sink_pad.is_linked() {
+                    return;
+                }
+
+                if let Err(e) = pad.link(&sink_pad) {
+                    warn!("Could not link the decoded video: {e}");
+                }
+            });
+
+            None
+        }
+        CameraSource::Portal => {
+            let camera_fd = camera_fd.ok_or(QrScanError::NoCameraAccess)?;
+            Some(
+                gst::ElementFactory::make("pipewiresrc")
+                    .property("fd", camera_fd.as_raw_fd())
+                    .property("do-timestamp", true) // helps with mobile cameras
+                    .build()
+                    .map_err(|_| QrScanError::ElementCreation("pipewiresrc"))?,
+            )
+        }
+        CameraSource::Device(device) => Some(
+            device
+                .create_element(None)
+                .map_err(|_| QrScanError::ElementCreation("camera device source"))?,
+        ),
+        CameraSource::Auto => Some(make("autovideosrc")?),
+        CameraSource::TestPattern => Some(
+            gst::ElementFactory::make("videotestsrc")
+                .property("is-live", true)
+                .build()
+                .map_err(|_| QrScanError::ElementCreation("videotestsrc"))?,
+        ),
+    };
+
+    if let Some(src) = static_src {
+        pipeline.add(&src).map_err(QrScanError::PipelineSetup)?;
+        src.link(&convert).map_err(QrScanError::PipelineSetup)?;
+    }
+
+    Ok(ScanPipeline {
+        pipeline,
+        display_sink,
+        decode_sink,
+        decode_valve,
+    })
+}
+
+/// Width and height of the frame held by `sample`
+fn sample_dimensions(sample: &gst::Sample) -> Result<(u32, u32), gst::FlowError> {
+    let caps = sample.caps().ok_or(gst::FlowError::Error)?;
+    let s = caps.structure(0).ok_or(gst::FlowError::Error)?;
+    Ok((
+        s.get::<i32>("width").map_err(|_| gst::FlowError::Error)? as u32,
+        s.get::<i32>("height").map_err(|_| gst::FlowError::Error)? as u32,
+    ))
+}
+
+fn qr_scan_view<'a>(
+    display_frame: &'a Option<Box<image::Handle>>,
+    batch_mode: bool,
+    sources: &'a [CameraSource],
+    source: &'a CameraSource,
+    resolution: Resolution,
+) -> Element<'a, Message> {
     let camera_display = if let Some(handle) = display_frame {
         container(
             image(handle.as_ref().clone())
@@ -441,10 +975,44 @@ fn qr_scan_view<'a>(display_frame: &'a Option<Box<image::Handle>>) -> Element<'a
         )
         .align_x(iced::alignment::Horizontal::Left)
         .align_y(iced::alignment::Vertical::Top),
-        container(text("Point camera at QR code").size(style::font_size::TITLE))
-            .width(Length::Fill)
-            .align_x(iced::alignment::Horizontal::Center)
-            .align_y(iced::alignment::Vertical::Top)
+        container(
+            text(if batch_mode {
+                "Point camera at each QR code"
+            } else {
+                "Point camera at QR code"
+            })
+            .size(style::font_size::TITLE)
+        )
+        .width(Length::Fill)
+        .align_x(iced::alignment::Horizontal::Center)
+        .align_y(iced::alignment::Vertical::Top),
+        container(
+            checkbox(batch_mode)
+                .label("Batch mode")
+                .on_toggle(Message::ToggleBatchMode)
+        )
+        .width(Length::Fill)
+        .align_x(iced::alignment::Horizontal::Right)
+        .align_y(iced::alignment::Vertical::Top),
+        container(
+            row![
+                pick_list(Some(source), sources, |v: &_| v.to_string())
+                    .on_select(Message::SourceSelected)
+                    .padding(8),
+                pick_list(Some(&resolution), Resolution::ALL, |v: &_| v.to_string())
+                    .on_select(Message::ResolutionSelected)
+                    .padding(8),
+                button(text("Scan from video file…").size(style::font_size::BODY))
+                    .on_press(Message::OpenVideoFileSelection)
+                    .padding(8)
+                    .style(style::secondary_button),
+            ]
+            .spacing(style::spacing::SMALL)
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .align_x(iced::alignment::Horizontal::Center)
+        .align_y(iced::alignment::Vertical::Bottom)
     ])
     .width(Length::Fill)
     .height(Length::Fill)
@@ -458,6 +1026,88 @@ fn qr_scan_view<'a>(display_frame: &'a Option<Box<image::Handle>>) -> Element<'a
         .into()
 }
 
+/// Side panel listing the entries collected in batch mode
+fn pending_entries_view<'a>(pending: &'a [PendingEntry]) -> Element<'a, Message> {
+    let entries_list = pending.iter().enumerate().fold(
+        column![].spacing(style::spacing::SMALL),
+        |col, (index, pending)| {
+            let preview: Element<Message> = match &pending.preview {
+                Some(handle) => image(handle.clone()).width(64).height(48).into(),
+                None => icons::get_icon("qr-symbolic", 21).into(),
+            };
+
+            col.push(
+                container(
+                    row![
+                        preview,
+                        column![
+                            text(&pending.entry.name)
+                                .wrapping(text::Wrapping::Glyph)
+                                .size(style::font_size::MEDIUM),
+                            text(
+                                pending
+                                    .entry
+                                    .issuer
+                                    .as_deref()
+                                    .unwrap_or(&pending.entry.account_name)
+                            )
+                            .size(style::font_size::SMALL)
+                            .style(style::muted_text),
+                        ]
+                        .spacing(style::spacing::TINY)
+                        .width(Length::Fill),
+                        button(icons::get_icon("user-trash-full-symbolic", 21))
+                            .on_press(Message::RemovePending(index))
+                            .padding(8)
+                            .style(style::secondary_button),
+                    ]
+                    .spacing(style::spacing::SMALL)
+                    .align_y(iced::Alignment::Center),
+                )
+                .padding(8)
+                .style(style::entry_card),
+            )
+        },
+    );
+
+    let content: Element<Message> = if pending.is_empty() {
+        container(
+            text("Detected codes will show up here")
+                .size(style::font_size::BODY)
+                .style(style::muted_text),
+        )
+        .center(Length::Fill)
+        .into()
+    } else {
+        scrollable(entries_list).height(Length::Fill).into()
+    };
+
+    container(
+        column![
+            text(format!("{} Pending", pending.len())).size(style::font_size::TITLE),
+            content,
+            row![
+                button(text("Discard").size(style::font_size::BODY))
+                    .on_press_maybe((!pending.is_empty()).then_some(Message::DiscardPending))
+                    .padding(8)
+                    .style(style::secondary_button),
+                space().width(Length::Fill),
+                button(text("Save All").size(style::font_size::BODY))
+                    .on_press_maybe((!pending.is_empty()).then_some(Message::FinishBatch))
+                    .padding(8)
+                    .style(style::primary_button),
+            ]
+            .align_y(iced::Alignment::Center),
+        ]
+        .spacing(style::spacing::MEDIUM)
+        .padding(16),
+    )
+    .width(320)
+    .height(Length::Fill)
+    .style(style::card_container)
+    .into()
+}
+
 //
 // SUBSCRIPTIONS
 //
@@ -479,3 +1129,138 @@ fn handle_event(event: event::Event, _: event::Status, _: iced::window::Id) -> O
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// What the fixture images of `clockode-core` encode
+    const URI: &str =
+        "otpauth://totp/Clockode:alice@example.com?secret=JBSWY3DPEHPK3PXP&issuer=Clockode";
+
+    /// Runs fixture images of `clockode-core` through the camera decoder, padded to the size
+    /// of the frames the decode branch hands it
+    #[test]
+    fn decodes_difficult_frames() {
+        let (width, height) = Resolution::default().decode_dimensions();
+
+        for name in ["inverted.png", "low-contrast-photo.png"] {
+            let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("clockode-core/tests/fixtures")
+                .join(name);
+            let fixture = ::image::open(path).unwrap().to_luma8();
+
+            // centered, the rest of the frame filled with the colour of the fixture's corner
+            let mut frame =
+                GrayImage::from_pixel(width as u32, height as u32, *fixture.get_pixel(0, 0));
+            ::image::imageops::replace(
+                &mut frame,
+                &fixture,
+                (i64::from(width) - i64::from(fixture.width())) / 2,
+                (i64::from(height) - i64::from(fixture.height())) / 2,
+            );
+
+            let content = QrScanPage::decode_qr(FrameData {
+                width: frame.width(),
+                height: frame.height(),
+                data: frame.into_raw(),
+            });
+            assert_eq!(content.as_deref(), Some(URI), "{name}");
+        }
+    }
+
+    /// Builds the pipeline on the `videotestsrc` pattern and checks frames reach the decoder
+    /// with the expected size
+    #[test]
+    #[ignore = "needs GStreamer and its base plugins, run with `cargo test -- --ignored`"]
+    fn test_pattern_reaches_decode_branch() {
+        gst::init().expect("GStreamer should be installed");
+
+        let resolution = Resolution::default();
+        let ScanPipeline {
+            pipeline,
+            decode_sink,
+            ..
+        } = build_pipeline(&CameraSource::TestPattern, resolution, None)
+            .expect("the pipeline should build from installed plugins");
+
+        pipeline
+            .set_state(gst::State::Playing)
+            .expect("pipeline should start playing");
+        let sample = decode_sink.try_pull_sample(gst::ClockTime::from_seconds(10));
+        let _ = pipeline.set_state(gst::State::Null);
+
+        let sample = sample.expect("no frame reached the decode branch");
+        let (width, height) = sample_dimensions(&sample).unwrap();
+        assert_eq!(
+            (width as i32, height as i32),
+            resolution.decode_dimensions()
+        );
+    }
+
+    /// Runs the page on the test pattern: frames reach the decoder at most [`DECODE_FPS`] times
+    /// a second, and none do anymore once a code is accepted
+    #[test]
+    #[ignore = "needs GStreamer and its base plugins, run with `cargo test -- --ignored`"]
+    fn accepted_code_closes_the_decode_branch() {
+        gst::init().expect("GStreamer should be installed");
+
+        let mut page = QrScanPage {
+            state: State::AskingPermission,
+            sources: vec![CameraSource::TestPattern],
+            source: CameraSource::TestPattern,
+            resolution: Resolution::default(),
+            camera_fd: None,
+            generation: 0,
+            batch_mode: false,
+            pending: Vec::new(),
+            seen: HashSet::new(),
+        };
+        page.start_pipeline()
+            .expect("the pipeline should start on the test pattern");
+        let State::Permitted(state) = &page.state else {
+            panic!("the page should be scanning");
+        };
+        let frame_rx = state.frame_rx.clone();
+
+        // whether a frame reached the decoder within `timeout`
+        let next_frame = |timeout| {
+            smol::block_on(smol::future::or(
+                async { frame_rx.recv().await.is_ok() },
+                async {
+                    smol::Timer::after(timeout).await;
+                    false
+                },
+            ))
+        };
+
+        assert!(
+            next_frame(Duration::from_secs(10)),
+            "no frame reached the decode branch"
+        );
+        let started = Instant::now();
+        let mut frames = 0;
+        while started.elapsed() < Duration::from_secs(2) {
+            if next_frame(Duration::from_millis(100)) {
+                frames += 1;
+            }
+        }
+        assert!(
+            (1..=2 * DECODE_FPS + 1).contains(&frames),
+            "{frames} frames reached the decoder in 2s"
+        );
+
+        let action = page.update(Message::QrDetected(URI.to_string()), Instant::now());
+        assert!(matches!(action, Action::EntryDetected(_)));
+
+        // frames already past the valve still come through, then nothing does
+        std::thread::sleep(Duration::from_millis(500));
+        while frame_rx.try_recv().is_ok() {}
+        assert!(
+            !next_frame(Duration::from_secs(2)),
+            "a frame reached the decoder after the code was accepted"
+        );
+    }
+}
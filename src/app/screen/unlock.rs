@@ -9,19 +9,17 @@ use iced::{
     keyboard::{self, Key, Modifiers, key::Named},
     time::Instant,
     widget::{
-        button, column, container,
+        button, checkbox, column, container,
         operation::{focus_next, focus_previous},
         space, svg, text, text_input,
     },
 };
 
+use clockode_core::{ClockodeDatabase, unlock_database};
+
 use crate::{
     APP_ICON,
-    app::{
-        core::{ClockodeDatabase, unlock_database},
-        utils::style,
-        widgets::Toast,
-    },
+    app::{utils::style, widgets::Toast},
 };
 
 pub struct UnlockDatabase {
@@ -34,6 +32,8 @@ pub enum Message {
     Hotkey(Hotkey),
 
     UpdatePassword(String),
+    /// Toggles opening the vault without allowing any change
+    ToggleReadOnly(bool),
     Submit,
 
     DatabaseUnlocked(Box<Result<ClockodeDatabase, anywho::Error>>),
@@ -77,6 +77,9 @@ impl UnlockDatabase {
                         .size(style::font_size::MEDIUM)
                 ]
                 .spacing(style::spacing::TINY),
+                checkbox(self.inputs.read_only)
+                    .label("Open read-only")
+                    .on_toggle(Message::ToggleReadOnly),
                 button(
                     text("Unlock Database")
                         .size(style::font_size::MEDIUM)
@@ -135,8 +138,16 @@ impl UnlockDatabase {
                 self.inputs.password = v;
                 Action::None
             }
+            Message::ToggleReadOnly(read_only) => {
+                self.inputs.read_only = read_only;
+                Action::None
+            }
             Message::Submit => Action::Run(Task::perform(
-                unlock_database(self.db_path.clone(), self.inputs.password.clone().into()),
+                unlock_database(
+                    self.db_path.clone(),
+                    self.inputs.password.clone().into(),
+                    self.inputs.read_only,
+                ),
                 |res| Message::DatabaseUnlocked(Box::from(res)),
             )),
             Message::DatabaseUnlocked(res) => match *res {
@@ -155,6 +166,7 @@ impl UnlockDatabase {
 #[derive(Default)]
 struct PageInputs {
     password: String,
+    read_only: bool,
 }
 
 impl PageInputs {
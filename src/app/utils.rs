@@ -1,13 +1,12 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-mod input;
+mod database;
 mod qr;
+#[cfg(unix)]
+mod socket;
 pub mod style;
-mod time;
-mod database;
 
-pub use input::ALL_ALGORITHMS;
-pub use input::InputableClockodeEntry;
-pub use qr::read_qr_from_file;
-pub use time::get_time_until_next_totp_refresh;
 pub use database::watch_database;
+pub use qr::read_qr_from_clipboard;
+#[cfg(unix)]
+pub use socket::{bind_private_socket, runtime_socket_path};
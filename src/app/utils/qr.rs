@@ -1,17 +1,25 @@
 use anywho::anywho;
-use std::path::PathBuf;
+use clockode_core::read_qr_from_image;
+use image::{DynamicImage, RgbaImage};
 
-pub fn read_qr_from_file(path: PathBuf) -> Result<String, anywho::Error> {
-    let img = image::open(&path)?;
-    let img = img.to_luma8();
-    let mut img = rqrr::PreparedImage::prepare(img);
-    let grids = img.detect_grids();
+/// Decodes every QR code found in the image currently held by the system clipboard.
+///
+/// Blocks while talking to the clipboard, so it should be run outside of the UI thread.
+pub fn read_qr_from_clipboard() -> Result<Vec<String>, anywho::Error> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| anywho!("Could not access the clipboard: {}", e))?;
 
-    // Get the first QR code found
-    if let Some(grid) = grids.into_iter().next() {
-        let (_meta, content) = grid.decode()?;
-        Ok(content)
-    } else {
-        Err(anywho!("No QR code found in image"))
-    }
+    let clipboard_image = clipboard.get_image().map_err(|e| match e {
+        arboard::Error::ContentNotAvailable => anywho!("The clipboard doesn't hold an image"),
+        e => anywho!("Could not read the clipboard image: {}", e),
+    })?;
+
+    let img = RgbaImage::from_raw(
+        clipboard_image.width as u32,
+        clipboard_image.height as u32,
+        clipboard_image.bytes.into_owned(),
+    )
+    .ok_or_else(|| anywho!("The clipboard image is malformed"))?;
+
+    read_qr_from_image(&DynamicImage::ImageRgba8(img))
 }
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    fs::{DirBuilder, Permissions},
+    os::unix::fs::{DirBuilderExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+use anywho::anywho;
+use clockode_core::APP_ID;
+use smol::net::unix::UnixListener;
+use tracing::warn;
+
+/// Path of the socket with the given name in the runtime directory of the app,
+/// `None` when the platform has no runtime directory (`$XDG_RUNTIME_DIR`)
+pub fn runtime_socket_path(name: &str) -> Option<PathBuf> {
+    let runtime_dir = dirs::runtime_dir()?;
+
+    // Inside Flatpak only `app/<app id>` is shared between instances and with the host
+    if std::env::var_os("FLATPAK_ID").is_some() {
+        Some(runtime_dir.join("app").join(APP_ID).join(name))
+    } else {
+        Some(runtime_dir.join(APP_ID).join(name))
+    }
+}
+
+/// Binds the socket inside a directory only the current user can access, replacing a stale
+/// socket left behind by a crashed instance.
+///
+/// The returned guard removes the socket file once dropped.
+pub fn bind_private_socket(path: &Path) -> Result<(UnixListener, SocketFile), anywho::Error> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anywho!("Socket path has no parent directory"))?;
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    std::fs::set_permissions(dir, Permissions::from_mode(0o700))?;
+
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(anywho!("Another instance is already listening on {:?}", path));
+        }
+        std::fs::remove_file(path)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    Ok((listener, SocketFile(path.to_path_buf())))
+}
+
+/// Removes the socket file once the server stops
+pub struct SocketFile(PathBuf);
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.0) {
+            warn!("Could not remove the socket {:?}: {err}", self.0);
+        }
+    }
+}
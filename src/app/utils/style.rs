@@ -41,6 +41,14 @@ pub fn card_container(theme: &Theme) -> container::Style {
     }
 }
 
+/// Plain white background for the full window QR code, ignores the theme so it always has maximum contrast
+pub fn qr_fullscreen_container(_theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(iced::Color::WHITE.into()),
+        ..Default::default()
+    }
+}
+
 /// Entry card style - for TOTP entry items
 pub fn entry_card(theme: &Theme) -> container::Style {
     container::Style {
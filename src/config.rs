@@ -1,15 +1,99 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use std::path::PathBuf;
+
 use anywho::anywho;
 use iced::Theme;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+use clockode_core::BackupPolicy;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub theme: ColockodeTheme,
+    /// Whether other programs can ask for codes over the IPC socket while the vault is unlocked
+    #[serde(default)]
+    pub ipc_enabled: bool,
+    /// Programs whose IPC requests are answered without asking
+    #[serde(default)]
+    pub ipc_trusted_programs: Vec<TrustedProgram>,
+    /// Whether to serve the `dev.mariinkys.Clockode` D-Bus service on the session bus
+    #[serde(default)]
+    pub dbus_enabled: bool,
+    /// Whether launching Clockode again raises the open window instead of opening another one
+    #[serde(default)]
+    pub single_instance: bool,
+    /// Vaults shown next to the personal one, each unlocked with its own password
+    #[serde(default)]
+    pub shared_vaults: Vec<SharedVault>,
+    /// How many of the latest vault backups to keep, one is taken before every save
+    #[serde(default = "default_backups_kept")]
+    pub backups_kept: usize,
+    /// For how many days the last backup of each day is kept too
+    #[serde(default = "default_backup_days")]
+    pub backup_days: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: ColockodeTheme::default(),
+            ipc_enabled: false,
+            ipc_trusted_programs: Vec::new(),
+            dbus_enabled: false,
+            single_instance: false,
+            shared_vaults: Vec::new(),
+            backups_kept: default_backups_kept(),
+            backup_days: default_backup_days(),
+        }
+    }
+}
+
+fn default_backups_kept() -> usize {
+    BackupPolicy::default().keep_last
+}
+
+fn default_backup_days() -> u32 {
+    BackupPolicy::default().keep_days
+}
+
+/// A program allowed to request codes over the IPC socket without asking
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrustedProgram {
+    pub exe: PathBuf,
+    /// Script the executable runs, so trusting one script doesn't trust its whole interpreter
+    #[serde(default)]
+    pub script: Option<PathBuf>,
+}
+
+/// A vault mounted next to the personal one, like a team vault of service account codes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SharedVault {
+    pub path: PathBuf,
+    /// Whether its entries can be changed, shared vaults are opened read-only otherwise
+    #[serde(default)]
+    pub writable: bool,
+}
+
+impl SharedVault {
+    /// Name to show for the vault, its file name without the extension
+    pub fn name(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
 }
 
 impl Config {
+    /// Which backups are taken before every save of a vault
+    pub fn backup_policy(&self) -> BackupPolicy {
+        BackupPolicy {
+            keep_last: self.backups_kept,
+            keep_days: self.backup_days,
+        }
+    }
+
     pub async fn load(app_id: &str) -> Result<Self, anywho::Error> {
         use dirs;
         use std::fs;
@@ -47,6 +47,7 @@ impl IconCache {
         bundle!("window-close-symbolic", 21);
         bundle!("qr-symbolic", 21);
         bundle!("camera-photo-symbolic", 48);
+        bundle!("system-lock-screen-symbolic", 21);
 
         Self { cache }
     }
@@ -1,13 +1,17 @@
 // SPDX-License-Identifier: GPL-3.0-only
+// No console window on Windows. That also leaves the command line interface without a console
+// to print to, and shells don't wait for GUI programs to exit, so it's only supported on Unix.
 #![windows_subsystem = "windows"]
 
-use crate::app::Clockode;
+use clap::Parser;
+use clockode_core::APP_ID;
+
+use crate::app::{Clockode, cli::Cli};
 
 mod app;
 mod config;
 mod icons;
 
-const APP_ID: &str = "dev.mariinkys.Clockode";
 const APP_ICON: &[u8] = include_bytes!("../resources/icons/hicolor/scalable/apps/icon.svg");
 
 /// SEE: https://github.com/pop-os/cosmic-bg/pull/73
@@ -30,6 +34,21 @@ mod malloc {
 }
 
 fn main() -> iced::Result {
+    let cli = Cli::parse();
+
+    if let Some(command) = cli.command {
+        // keep stdout for the command output, only warnings and errors go to stderr
+        tracing_subscriber::fmt()
+            .with_writer(std::io::stderr)
+            .with_env_filter(
+                tracing_subscriber::EnvFilter::try_from_default_env()
+                    .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+            )
+            .init();
+
+        std::process::exit(app::cli::run(command));
+    }
+
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
@@ -37,6 +56,33 @@ fn main() -> iced::Result {
         )
         .init();
 
+    let uris = cli.otpauth_uris();
+
+    // Hand the URIs over to the window that is already open instead of opening a second one,
+    // a plain launch only raises it if the single instance mode is enabled
+    #[cfg(unix)]
+    {
+        let command = if !uris.is_empty() {
+            Some(app::instance::Command::OpenUris { uris: uris.clone() })
+        } else {
+            smol::block_on(config::Config::load(APP_ID))
+                .is_ok_and(|cfg| cfg.single_instance)
+                .then_some(app::instance::Command::Raise)
+        };
+
+        if let Some(command) = command {
+            match app::instance::forward(&command) {
+                Ok(()) => {
+                    tracing::info!("Handed the launch over to the running instance");
+                    return Ok(());
+                }
+                Err(err) => {
+                    tracing::info!("No running instance to hand the launch over to ({err}), opening a new window")
+                }
+            }
+        }
+    }
+
     // Prevents glibc from hoarding memory via memory fragmentation.
     #[cfg(target_env = "gnu")]
     malloc::limit_mmap_threshold();
@@ -65,7 +111,7 @@ fn main() -> iced::Result {
     };
 
     iced::application::timed(
-        Clockode::new,
+        move || Clockode::new(uris.clone()),
         Clockode::update,
         Clockode::subscription,
         Clockode::view,